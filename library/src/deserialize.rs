@@ -1,20 +1,46 @@
 mod array;
 mod arrayptr;
+mod consistent;
 mod deserializetrait;
 mod eager;
 mod error;
+mod errorpath;
+#[cfg(feature = "json")]
+mod json;
+mod layout;
 mod lazy;
 mod linkedlist;
 mod postsizedarray;
+mod pretty;
 mod ptr;
+mod relptr;
+mod remote;
 mod string;
+mod taggedptr;
+mod validate;
+mod version;
+mod versioned;
 mod zerolengtharray;
 
 pub use arrayptr::ArrayPtr;
-pub use deserializetrait::Deserialize;
+pub use consistent::{Consistent, DEFAULT_MAX_READ_ATTEMPTS};
+pub use deserializetrait::{Bitfield, Deserialize};
 pub use eager::Eager;
 pub use error::Error;
+pub use errorpath::{ErrorPath, PathSegment};
+#[cfg(feature = "json")]
+pub use json::{serialize_array, to_json};
+pub use layout::{layout_table, FieldLayout, LayoutTable, StructLayout};
 pub use lazy::LazyDeserialize;
+pub use linkedlist::{BoundedLinkedList, DEFAULT_MAX_LINKED_LIST_LENGTH};
 pub use postsizedarray::PostSizedArray;
+pub use pretty::{Pretty, PrettyPrint, DEFAULT_MAX_DEPTH};
 pub use ptr::Ptr;
+pub use relptr::RelPtr;
+pub use remote::RemoteRef;
+pub(crate) use string::read_c_string;
+pub use taggedptr::TaggedPtr;
+pub use validate::Validate;
+pub use version::{ParseVersionError, Version};
+pub use versioned::VersionedDeserialize;
 pub use zerolengtharray::ZeroLengthArray;
@@ -1,27 +1,34 @@
-use crate::memory::{Address, MemoryReader};
+use crate::memory::{Address, Architecture, MemoryReader};
 
-use super::Deserialize;
 use super::Error as DeserializeError;
+use super::{Deserialize, PathSegment};
 
 impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
-    const NUM_BYTES: usize = T::NUM_BYTES * N;
-    const ALIGNMENT: usize = T::ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        T::num_bytes::<A>() * N
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        T::alignment::<A>()
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
         address: Address,
     ) -> Result<Self, DeserializeError> {
         let padded_element_size: usize =
-            Address::new(T::NUM_BYTES).align_forward(T::ALIGNMENT).raw();
+            Address::new(T::num_bytes::<M::Architecture>())
+                .align_forward(T::alignment::<M::Architecture>())
+                .raw();
 
         std::array::try_from_fn(|index| {
-            T::deserialize(reader, address + index * padded_element_size)
-                .map_err(|error| {
-                    DeserializeError::WithContext(
-                        Box::new(error),
-                        format!("[{N}].{index}"),
-                    )
+            let element_addr = address + index * padded_element_size;
+            T::deserialize(reader, element_addr).map_err(|error| {
+                error.within(PathSegment::Index {
+                    index,
+                    address: element_addr,
                 })
+            })
         })
     }
 }
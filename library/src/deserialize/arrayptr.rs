@@ -1,25 +1,50 @@
+use std::fmt;
 use std::marker::PhantomData;
 
-use crate::memory::{Address, MemoryReader};
+use crate::memory::{Address, Architecture, MemoryReader};
 
-use super::ptr::{PTR_ALIGNMENT, PTR_NUM_BYTES};
-use super::Deserialize;
 use super::Error as DeserializeError;
+use super::{Deserialize, PathSegment};
 
-#[derive(Debug)]
 pub struct ArrayPtr<T: Deserialize> {
+    slot: Option<Address>,
     address: Address,
     deref_type: PhantomData<T>,
 }
 
 impl<T: Deserialize> ArrayPtr<T> {
+    /// Creates a pointer to an array starting at `address` that was not read
+    /// from a pointer in memory.
     #[must_use]
     pub fn new(address: Address) -> Self {
         Self {
+            slot: None,
             address,
             deref_type: PhantomData,
         }
     }
+
+    /// Gets the address of the first element of the array.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gets the address of the pointer this was deserialized from, or `None`
+    /// if it was not deserialized from a pointer.
+    #[must_use]
+    pub fn slot(&self) -> Option<Address> {
+        self.slot
+    }
+}
+
+impl<T: Deserialize> fmt::Debug for ArrayPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.slot {
+            Some(slot) => write!(f, "ArrayPtr({slot} -> {})", self.address),
+            None => write!(f, "ArrayPtr({})", self.address),
+        }
+    }
 }
 
 impl<T: Deserialize> ArrayPtr<T> {
@@ -29,9 +54,24 @@ impl<T: Deserialize> ArrayPtr<T> {
         index: usize,
     ) -> Result<T, DeserializeError> {
         let padded_element_size: usize =
-            Address::new(T::NUM_BYTES).align_forward(T::ALIGNMENT).raw();
+            Address::new(T::num_bytes::<M::Architecture>())
+                .align_forward(T::alignment::<M::Architecture>())
+                .raw();
 
-        T::deserialize(reader, self.address + index * padded_element_size)
+        let element_addr = self.address + index * padded_element_size;
+        T::deserialize(reader, element_addr).map_err(|error| {
+            let error = error.within(PathSegment::Index {
+                index,
+                address: element_addr,
+            });
+            match self.slot {
+                Some(slot) => error.within(PathSegment::Deref {
+                    from: slot,
+                    to: self.address,
+                }),
+                None => error,
+            }
+        })
     }
 
     pub fn deref<M: MemoryReader>(
@@ -40,21 +80,19 @@ impl<T: Deserialize> ArrayPtr<T> {
         size: usize,
     ) -> Result<Vec<T>, DeserializeError> {
         (0..size)
-            .map(|index| {
-                self.nth_element(reader, index).map_err(|error| {
-                    DeserializeError::WithContext(
-                        Box::new(error),
-                        format!("&[{size}].{index}"),
-                    )
-                })
-            })
+            .map(|index| self.nth_element(reader, index))
             .collect()
     }
 }
 
 impl<T: Deserialize> Deserialize for Option<ArrayPtr<T>> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -62,6 +100,7 @@ impl<T: Deserialize> Deserialize for Option<ArrayPtr<T>> {
     ) -> Result<Self, DeserializeError> {
         Ok(Option::<Address>::deserialize(reader, address)?.map(
             |pointed_addr| ArrayPtr {
+                slot: Some(address),
                 address: pointed_addr,
                 deref_type: PhantomData,
             },
@@ -70,8 +109,13 @@ impl<T: Deserialize> Deserialize for Option<ArrayPtr<T>> {
 }
 
 impl<T: Deserialize> Deserialize for ArrayPtr<T> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
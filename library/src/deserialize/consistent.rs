@@ -0,0 +1,152 @@
+use crate::memory::buffered::BufferedMemoryReader;
+use crate::memory::{
+    Address, Architecture, MemoryReader, VariableLengthAddressRange,
+};
+
+use super::Deserialize;
+use super::Error as DeserializeError;
+
+/// The default maximum number of times a [`Consistent`] value is read.
+pub const DEFAULT_MAX_READ_ATTEMPTS: usize = 4;
+
+/// A value that is only deserialized once two consecutive reads of its bytes
+/// agree, to avoid torn reads of memory that is being written concurrently.
+///
+/// The bytes of `T` are read up to `MAX_ATTEMPTS` times with
+/// [`read_vec_uncached`](MemoryReader::read_vec_uncached), so that readers that
+/// cache or buffer memory still see concurrent writes. Values that `T` points
+/// to are read once, after a consistent read of `T` itself.
+pub struct Consistent<
+    T: Deserialize,
+    const MAX_ATTEMPTS: usize = DEFAULT_MAX_READ_ATTEMPTS,
+> {
+    pub value: T,
+}
+
+impl<T: Deserialize, const MAX_ATTEMPTS: usize> Deserialize
+    for Consistent<T, MAX_ATTEMPTS>
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        T::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        T::alignment::<A>()
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        const {
+            assert!(MAX_ATTEMPTS >= 2, "MAX_ATTEMPTS must be at least 2");
+        }
+
+        let range = VariableLengthAddressRange {
+            start: address,
+            num_bytes: T::num_bytes::<M::Architecture>(),
+        };
+
+        let mut previous = reader.read_vec_uncached(range)?;
+        for _ in 1..MAX_ATTEMPTS {
+            let current = reader.read_vec_uncached(range)?;
+            if current == previous {
+                let mut buffered_reader =
+                    BufferedMemoryReader::with_buffer(reader, address, current);
+                return Ok(Self {
+                    value: T::deserialize(&mut buffered_reader, address)?,
+                });
+            }
+            previous = current;
+        }
+
+        Err(DeserializeError::InconsistentReadError(
+            address,
+            MAX_ATTEMPTS,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::deserialize::Deserialize;
+    use crate::memory::caching::CachingMemoryReader;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    // Memory that is being written concurrently: every byte holds the number
+    // of reads made so far, until that reaches `settled_after`.
+    struct ChangingMemory {
+        num_reads: u8,
+        settled_after: u8,
+    }
+
+    impl MemoryReader for ChangingMemory {
+        type Architecture = X86_64;
+
+        fn read_vec(
+            &mut self,
+            range: VariableLengthAddressRange,
+        ) -> io::Result<Vec<u8>> {
+            self.num_reads = self.num_reads.saturating_add(1);
+            Ok(vec![
+                self.num_reads.min(self.settled_after);
+                range.num_bytes
+            ])
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Timer {
+        elapsed: Consistent<u32>,
+    }
+
+    #[test]
+    fn reads_until_consecutive_reads_agree() {
+        let mut memory = ChangingMemory {
+            num_reads: 0,
+            settled_after: 3,
+        };
+        let value =
+            Consistent::<u32>::deserialize(&mut memory, Address::new(0x1000))
+                .ok()
+                .unwrap();
+        assert_eq!(value.value, 0x0303_0303);
+        assert_eq!(memory.num_reads, 4);
+    }
+
+    #[test]
+    fn reports_memory_that_never_settles() {
+        let mut memory = ChangingMemory {
+            num_reads: 0,
+            settled_after: u8::MAX,
+        };
+        let error =
+            Consistent::<u32>::deserialize(&mut memory, Address::new(0x1000))
+                .err()
+                .unwrap();
+        assert!(matches!(
+            error,
+            DeserializeError::InconsistentReadError(address, 4)
+                if address == Address::new(0x1000)
+        ));
+    }
+
+    #[test]
+    fn bypasses_cache_and_enclosing_struct() {
+        let mut reader = CachingMemoryReader::<_, 16>::new(ChangingMemory {
+            num_reads: 0,
+            settled_after: 3,
+        });
+        // Cache the page, which then holds the bytes of the first read.
+        u32::deserialize(&mut reader, Address::new(0x1000)).unwrap();
+
+        let timer = Timer::deserialize(&mut reader, Address::new(0x1000))
+            .ok()
+            .unwrap();
+        assert_eq!(timer.elapsed.value, 0x0303_0303);
+    }
+}
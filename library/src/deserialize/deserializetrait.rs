@@ -1,6 +1,10 @@
-use std::mem::{align_of, size_of};
+use std::cmp::min;
+use std::mem::size_of;
 
-use crate::memory::{Address, AddressRange, MemoryReader};
+use crate::memory::{
+    Address, AddressRange, Architecture, Endianness, MemoryReader,
+    VariableLengthAddressRange,
+};
 
 use super::Error as DeserializeError;
 
@@ -8,18 +12,28 @@ pub use grub_split_macros::*;
 
 /// Trait for types that can be deserialized from a fixed-length contiguous byte
 /// sequence.
+///
+/// The layout of a type may depend on the [`Architecture`] of the process it is
+/// read from; values are always deserialized according to the architecture of
+/// the reader they are read with. This is why sizes and alignments are
+/// functions of the architecture rather than associated constants; pass
+/// [`Native`](crate::memory::Native) as `A` to get the layout on the machine
+/// running this code.
 pub trait Deserialize: Sized {
-    /// The number of bytes that are required for deserialization.
-    const NUM_BYTES: usize;
+    /// The number of bytes that are required for deserialization when the
+    /// target has architecture `A`.
+    fn num_bytes<A: Architecture>() -> usize;
 
-    /// The required alignment of the bytes. Must be a power of two.
-    const ALIGNMENT: usize;
+    /// The required alignment of the bytes when the target has architecture
+    /// `A`. Must be a power of two.
+    fn alignment<A: Architecture>() -> usize;
 
     /// Attempts to deserialize an instance from memory starting at `address`
     /// using `reader`.
     ///
-    /// If `address` is not aligned to [`ALIGNMENT`](Deserialize::ALIGNMENT),
-    /// behavior is implementation-defined and may be erroneous.
+    /// If `address` is not aligned to [`alignment`](Deserialize::alignment)
+    /// for the reader's architecture, behavior is implementation-defined and
+    /// may be erroneous.
     ///
     /// Returns an [`Error`](super::Error) if deserialization fails.
     fn deserialize<M: MemoryReader>(
@@ -28,38 +42,124 @@ pub trait Deserialize: Sized {
     ) -> Result<Self, DeserializeError>;
 }
 
-macro_rules! deserialize_int_impl {
+macro_rules! deserialize_number_impl {
     ($T:ty) => {
         impl Deserialize for $T {
-            const NUM_BYTES: usize = size_of::<$T>();
-            const ALIGNMENT: usize = align_of::<$T>();
+            fn num_bytes<A: Architecture>() -> usize {
+                size_of::<$T>()
+            }
+
+            fn alignment<A: Architecture>() -> usize {
+                min(size_of::<$T>(), A::MAX_ALIGNMENT)
+            }
 
             fn deserialize<M: MemoryReader>(
                 reader: &mut M,
                 address: Address,
             ) -> Result<Self, DeserializeError> {
                 let range =
-                    AddressRange::<{ Self::NUM_BYTES }> { start: address };
-                Ok(<$T>::from_ne_bytes(reader.read(range)?))
+                    AddressRange::<{ size_of::<$T>() }> { start: address };
+                let bytes = reader.read(range)?;
+                Ok(match M::Architecture::ENDIANNESS {
+                    Endianness::Big => <$T>::from_be_bytes(bytes),
+                    Endianness::Little => <$T>::from_le_bytes(bytes),
+                })
             }
         }
     };
 }
 
-deserialize_int_impl!(u8);
-deserialize_int_impl!(i8);
-deserialize_int_impl!(u16);
-deserialize_int_impl!(i16);
-deserialize_int_impl!(u32);
-deserialize_int_impl!(i32);
-deserialize_int_impl!(u64);
-deserialize_int_impl!(i64);
-deserialize_int_impl!(usize);
-deserialize_int_impl!(isize);
+deserialize_number_impl!(u8);
+deserialize_number_impl!(i8);
+deserialize_number_impl!(u16);
+deserialize_number_impl!(i16);
+deserialize_number_impl!(u32);
+deserialize_number_impl!(i32);
+deserialize_number_impl!(u64);
+deserialize_number_impl!(i64);
+deserialize_number_impl!(f32);
+deserialize_number_impl!(f64);
+
+/// Reads a pointer-sized unsigned integer starting at `address`, laid out
+/// according to the reader's architecture.
+pub(crate) fn read_pointer_sized<M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+) -> Result<u64, DeserializeError> {
+    let width = M::Architecture::POINTER_WIDTH;
+    const {
+        assert!(
+            M::Architecture::POINTER_WIDTH <= size_of::<u64>(),
+            "pointers may be at most 8 bytes wide",
+        );
+    }
+
+    let bytes = reader.read_vec(VariableLengthAddressRange {
+        start: address,
+        num_bytes: width,
+    })?;
+    let mut buffer = [0; size_of::<u64>()];
+    Ok(match M::Architecture::ENDIANNESS {
+        Endianness::Big => {
+            buffer[size_of::<u64>() - width..].copy_from_slice(&bytes);
+            u64::from_be_bytes(buffer)
+        }
+        Endianness::Little => {
+            buffer[..width].copy_from_slice(&bytes);
+            u64::from_le_bytes(buffer)
+        }
+    })
+}
+
+impl Deserialize for usize {
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        Ok(read_pointer_sized(reader, address)?.try_into()?)
+    }
+}
+
+impl Deserialize for isize {
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        // Sign-extend the value from the target's pointer width to 64 bits.
+        let unused_bits =
+            8 * (size_of::<u64>() - M::Architecture::POINTER_WIDTH);
+        let raw = read_pointer_sized(reader, address)? << unused_bits;
+        Ok(
+            (i64::from_ne_bytes(raw.to_ne_bytes()) >> unused_bits)
+                .try_into()?,
+        )
+    }
+}
 
 impl Deserialize for bool {
-    const NUM_BYTES: usize = 1;
-    const ALIGNMENT: usize = 1;
+    fn num_bytes<A: Architecture>() -> usize {
+        1
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        1
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -69,3 +169,86 @@ impl Deserialize for bool {
         Ok(reader.read(range)?[0] != 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{X86, X86_64};
+
+    use super::*;
+
+    // A big-endian 32-bit architecture, which no supported target uses.
+    struct BigEndian32;
+
+    impl Architecture for BigEndian32 {
+        const POINTER_WIDTH: usize = 4;
+        const MAX_ALIGNMENT: usize = 4;
+        const ENDIANNESS: Endianness = Endianness::Big;
+    }
+
+    #[test]
+    fn reads_numbers_in_target_byte_order() {
+        let mut memory = TestMemory::<BigEndian32>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &[0x12, 0x34, 0x56, 0x78]);
+        memory.write_bytes(address + 4, &[0xff, 0xff, 0xff, 0xfe]);
+
+        assert_eq!(
+            u32::deserialize(&mut memory, address).unwrap(),
+            0x1234_5678
+        );
+        assert_eq!(u16::deserialize(&mut memory, address).unwrap(), 0x1234);
+        assert_eq!(i32::deserialize(&mut memory, address + 4).unwrap(), -2);
+        assert_eq!(
+            usize::deserialize(&mut memory, address).unwrap(),
+            0x1234_5678
+        );
+        assert_eq!(isize::deserialize(&mut memory, address + 4).unwrap(), -2);
+    }
+
+    #[test]
+    fn reads_pointer_sized_integers_with_target_width() {
+        let mut memory = TestMemory::<X86>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &0x1234_5678_u32.to_le_bytes());
+        // Reading past the pointer width would change the values.
+        memory.write_bytes(address + 4, &[0xff; 4]);
+
+        assert_eq!(usize::num_bytes::<X86>(), 4);
+        assert_eq!(isize::alignment::<X86>(), 4);
+        assert_eq!(
+            usize::deserialize(&mut memory, address).unwrap(),
+            0x1234_5678
+        );
+        assert_eq!(
+            Address::deserialize(&mut memory, address).unwrap(),
+            Address::new(0x1234_5678)
+        );
+    }
+
+    #[test]
+    fn sign_extends_isize_from_target_width() {
+        let mut memory = TestMemory::<X86>::new(Address::new(0x1000));
+        let negative = memory.alloc(4);
+        memory.write_bytes(negative, &0xffff_fffe_u32.to_le_bytes());
+        let positive = memory.alloc(4);
+        memory.write_bytes(positive, &0x7fff_ffff_u32.to_le_bytes());
+
+        assert_eq!(isize::deserialize(&mut memory, negative).unwrap(), -2);
+        assert_eq!(
+            isize::deserialize(&mut memory, positive).unwrap(),
+            0x7fff_ffff
+        );
+        assert_eq!(
+            usize::deserialize(&mut memory, negative).unwrap(),
+            0xffff_fffe
+        );
+    }
+
+    #[test]
+    fn aligns_integers_to_target_max_alignment() {
+        assert_eq!(u64::alignment::<X86>(), 4);
+        assert_eq!(u64::alignment::<X86_64>(), 8);
+        assert_eq!(u16::alignment::<X86>(), 2);
+    }
+}
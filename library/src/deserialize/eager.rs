@@ -1,4 +1,4 @@
-use crate::memory::{Address, MemoryReader};
+use crate::memory::{Address, Architecture, MemoryReader};
 
 use super::Error as DeserializeError;
 use super::{Deserialize, LazyDeserialize};
@@ -7,9 +7,25 @@ pub struct Eager<T: LazyDeserialize> {
     pub value: T::Deserialized,
 }
 
+impl<T: LazyDeserialize> Clone for Eager<T>
+where
+    T::Deserialized: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+        }
+    }
+}
+
 impl<T: LazyDeserialize> Deserialize for Eager<T> {
-    const NUM_BYTES: usize = T::NUM_BYTES;
-    const ALIGNMENT: usize = T::ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        T::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        T::alignment::<A>()
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -6,6 +6,8 @@ use std::{error, fmt, io};
 
 use crate::memory::Address;
 
+use super::{ErrorPath, PathSegment};
+
 /// An error that occurs while attempting to deserialize a type from memory.
 #[derive(Debug)]
 pub enum Error {
@@ -15,6 +17,10 @@ pub enum Error {
     /// A string was not encoded correctly in memory.
     EncodingError(Utf8Error),
 
+    /// Memory starting at the address changed between every pair of
+    /// consecutive reads, up to the given maximum number of reads.
+    InconsistentReadError(Address, usize),
+
     /// An integral type conversion failed.
     IntConversionError(TryFromIntError),
 
@@ -24,6 +30,14 @@ pub enum Error {
     /// An IO error occurred while attempting to read memory.
     IoError(io::Error),
 
+    /// A linked list contained a cycle. The address is that of the first node
+    /// that was visited twice.
+    LinkedListCycleError(Address),
+
+    /// A linked list contained more nodes than the maximum allowed length.
+    /// The address is that of the first node beyond the limit.
+    LinkedListLengthError(Address, usize),
+
     /// A pointer was unexpectedly null.
     NullPtrError(Address),
 
@@ -31,9 +45,44 @@ pub enum Error {
     /// terminated before reaching an implementation-dependent limit.
     UnterminatedCStringError(Address),
 
-    /// An error with arbitrary context (e.g. the property where the error was
-    /// encountered)
-    WithContext(Box<Error>, String),
+    /// An error together with the path to the value where it occurred.
+    ///
+    /// Use [`Error::within`] rather than constructing this directly so that
+    /// paths are extended instead of nested.
+    WithPath(Box<Error>, ErrorPath),
+}
+
+impl Error {
+    /// Records that this error occurred within `segment`, prepending it to the
+    /// error's path.
+    #[must_use]
+    pub fn within(self, segment: PathSegment) -> Self {
+        match self {
+            Self::WithPath(error, mut path) => {
+                path.push_front(segment);
+                Self::WithPath(error, path)
+            }
+            error => Self::WithPath(Box::new(error), ErrorPath::new(segment)),
+        }
+    }
+
+    /// Gets the underlying error, without any path.
+    #[must_use]
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            Self::WithPath(error, _) => error.root_cause(),
+            error => error,
+        }
+    }
+
+    /// Gets the path to the value where this error occurred, if known.
+    #[must_use]
+    pub fn path(&self) -> Option<&ErrorPath> {
+        match self {
+            Self::WithPath(_, path) => Some(path),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -43,18 +92,31 @@ impl fmt::Display for Error {
                 write!(f, "Overflow in address range starting at {address}")
             }
             Self::EncodingError(encoding_error) => encoding_error.fmt(f),
+            Self::InconsistentReadError(address, attempts) => write!(
+                f,
+                "Memory at {address} changed during each of {attempts} reads"
+            ),
             Self::IntConversionError(convert_error) => convert_error.fmt(f),
-            Self::InvalidStateError(message) => write!(f, "{}", &message),
+            Self::InvalidStateError(message) => write!(f, "{message}"),
             Self::IoError(io_error) => io_error.fmt(f),
+            Self::LinkedListCycleError(address) => {
+                write!(f, "Cycle in linked list at node {address}")
+            }
+            Self::LinkedListLengthError(address, max_length) => write!(
+                f,
+                "Linked list exceeded maximum length {max_length} at node \
+                 {address}"
+            ),
             Self::NullPtrError(address) => {
                 write!(f, "Unexpected null pointer at {address}")
             }
             Self::UnterminatedCStringError(address) => {
                 write!(f, "Unterminated C string beginnning at {address}")
             }
-            Self::WithContext(error, context) => {
-                write!(f, "{context}: {error}")
-            }
+            Self::WithPath(error, path) => match path.address() {
+                Some(address) => write!(f, "{path} (at {address}): {error}"),
+                None => write!(f, "{path}: {error}"),
+            },
         }
     }
 }
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::memory::Address;
+
+/// A single step taken while deserializing, used to describe where in a
+/// structure an error occurred.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    /// The whole of a struct named `struct_name` located at `address`.
+    Struct {
+        struct_name: &'static str,
+        address: Address,
+    },
+
+    /// The field `field_name` of a struct named `struct_name`, located at
+    /// `address`.
+    Field {
+        struct_name: &'static str,
+        field_name: &'static str,
+        address: Address,
+    },
+
+    /// The element at `index` of an array or list, located at `address`.
+    Index { index: usize, address: Address },
+
+    /// Following the pointer stored at `from` to the value at `to`.
+    Deref { from: Address, to: Address },
+}
+
+impl PathSegment {
+    /// Gets the address of the value this segment refers to.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        match *self {
+            Self::Struct { address, .. }
+            | Self::Field { address, .. }
+            | Self::Index { address, .. }
+            | Self::Deref { to: address, .. } => address,
+        }
+    }
+}
+
+/// The sequence of [`PathSegment`]s leading from the value being deserialized
+/// to the value where an error occurred.
+///
+/// Displays as e.g. `Image.class_cache.table[42]->Class.internals.name`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorPath {
+    segments: VecDeque<PathSegment>,
+}
+
+impl ErrorPath {
+    /// Creates a path consisting of the single segment `segment`.
+    #[must_use]
+    pub fn new(segment: PathSegment) -> Self {
+        Self {
+            segments: VecDeque::from([segment]),
+        }
+    }
+
+    /// Adds `segment` to the start of this path.
+    pub fn push_front(&mut self, segment: PathSegment) {
+        self.segments.push_front(segment);
+    }
+
+    /// Gets the segments of this path, from outermost to innermost.
+    pub fn segments(&self) -> impl Iterator<Item = &PathSegment> {
+        self.segments.iter()
+    }
+
+    /// Gets the address of the innermost value in this path, if there is one.
+    #[must_use]
+    pub fn address(&self) -> Option<Address> {
+        self.segments.back().map(PathSegment::address)
+    }
+}
+
+impl fmt::Display for ErrorPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Struct names are shown at the start of the path and after each
+        // pointer; elsewhere they are implied by the preceding field. A path
+        // that starts at a pointer has no name for the pointer itself, so it
+        // starts with the address it points to instead.
+        let mut show_struct_name = true;
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                PathSegment::Deref { to, .. } if i == 0 => {
+                    write!(f, "*{to}")?;
+                    show_struct_name = false;
+                    continue;
+                }
+                PathSegment::Struct { struct_name, .. } => {
+                    if show_struct_name {
+                        write!(f, "{struct_name}")?;
+                    }
+                }
+                PathSegment::Field {
+                    struct_name,
+                    field_name,
+                    ..
+                } => {
+                    if show_struct_name {
+                        write!(f, "{struct_name}")?;
+                    }
+                    write!(f, ".{field_name}")?;
+                }
+                PathSegment::Index { index, .. } => write!(f, "[{index}]")?,
+                PathSegment::Deref { .. } => write!(f, "->")?,
+            }
+            show_struct_name = matches!(segment, PathSegment::Deref { .. });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deserialize::{Deserialize, Error as DeserializeError};
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Pair {
+        first: u32,
+        second: u32,
+    }
+
+    fn path(segments: Vec<PathSegment>) -> ErrorPath {
+        ErrorPath {
+            segments: segments.into(),
+        }
+    }
+
+    #[test]
+    fn displays_fields_indices_and_pointers() {
+        let path = path(vec![
+            PathSegment::Field {
+                struct_name: "Image",
+                field_name: "class_cache",
+                address: Address::new(0x1000),
+            },
+            PathSegment::Field {
+                struct_name: "MonoInternalHashTable",
+                field_name: "table",
+                address: Address::new(0x1010),
+            },
+            PathSegment::Index {
+                index: 42,
+                address: Address::new(0x2150),
+            },
+            PathSegment::Deref {
+                from: Address::new(0x2150),
+                to: Address::new(0x3000),
+            },
+            PathSegment::Field {
+                struct_name: "Class",
+                field_name: "internals",
+                address: Address::new(0x3000),
+            },
+            PathSegment::Field {
+                struct_name: "ClassInternals",
+                field_name: "name",
+                address: Address::new(0x3048),
+            },
+        ]);
+        assert_eq!(
+            path.to_string(),
+            "Image.class_cache.table[42]->Class.internals.name"
+        );
+        assert_eq!(path.address(), Some(Address::new(0x3048)));
+    }
+
+    #[test]
+    fn displays_leading_pointer_as_its_target() {
+        let deref = PathSegment::Deref {
+            from: Address::new(0x0ff8),
+            to: Address::new(0x1000),
+        };
+        assert_eq!(path(vec![deref.clone()]).to_string(), "*0x1000");
+
+        let error =
+            DeserializeError::LinkedListCycleError(Address::new(0x1000))
+                .within(deref.clone());
+        assert_eq!(
+            error.to_string(),
+            "*0x1000 (at 0x1000): Cycle in linked list at node 0x1000"
+        );
+
+        let path = path(vec![
+            deref,
+            PathSegment::Field {
+                struct_name: "Node",
+                field_name: "next",
+                address: Address::new(0x1008),
+            },
+        ]);
+        assert_eq!(path.to_string(), "*0x1000.next");
+    }
+
+    #[test]
+    fn reports_field_when_struct_cannot_be_read_whole() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(4);
+
+        let error = Pair::deserialize(&mut memory, address).err().unwrap();
+        assert!(matches!(error.root_cause(), DeserializeError::IoError(_)));
+        assert_eq!(
+            error.path(),
+            Some(&path(vec![PathSegment::Field {
+                struct_name: "Pair",
+                field_name: "second",
+                address: address + 4,
+            }]))
+        );
+        assert!(error.to_string().starts_with("Pair.second (at 0x1004): "));
+
+        memory.write_bytes(address, &[1, 0, 0, 0, 2, 0, 0, 0]);
+        let pair = Pair::deserialize(&mut memory, address).ok().unwrap();
+        assert_eq!((pair.first, pair.second), (1, 2));
+    }
+}
@@ -0,0 +1,179 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::memory::Address;
+
+use super::Error as DeserializeError;
+use super::{
+    ArrayPtr, BoundedLinkedList, Consistent, Deserialize, Eager,
+    LazyDeserialize, PostSizedArray, Ptr, RelPtr, TaggedPtr, ZeroLengthArray,
+};
+
+/// Serializes `value` as pretty-printed JSON.
+///
+/// Addresses and lazy pointers are written as hexadecimal strings (e.g.
+/// `"0x1234"`) without being dereferenced; eager values are written as nested
+/// objects.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(value)
+}
+
+/// Serializes a fixed-size array of any length as a sequence.
+///
+/// `serde` only implements [`Serialize`] for arrays of up to 32 elements; use
+/// this with `#[serde(serialize_with = "...")]` for longer arrays.
+pub fn serialize_array<S: Serializer, T: Serialize, const N: usize>(
+    array: &[T; N],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(array)
+}
+
+impl Serialize for Address {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<T> Serialize for Ptr<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.address().serialize(serializer)
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> Serialize for TaggedPtr<T, MASK> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("TaggedPtr", 2)?;
+        state.serialize_field("address", &self.address())?;
+        state.serialize_field("tag", &self.tag())?;
+        state.end()
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> Serialize for RelPtr<T, W> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.address().serialize(serializer)
+    }
+}
+
+impl<T: Deserialize> Serialize for ArrayPtr<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.address().serialize(serializer)
+    }
+}
+
+impl<T: Deserialize> Serialize for ZeroLengthArray<T> {
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.address().serialize(serializer)
+    }
+}
+
+impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>> Serialize
+    for PostSizedArray<T, U>
+where
+    U: Serialize,
+    DeserializeError: From<<U as TryInto<usize>>::Error>,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("PostSizedArray", 2)?;
+        state.serialize_field("array_ptr", &self.array_ptr())?;
+        state.serialize_field("size", &self.size())?;
+        state.end()
+    }
+}
+
+impl<T: Deserialize + Serialize, const MAX_ATTEMPTS: usize> Serialize
+    for Consistent<T, MAX_ATTEMPTS>
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<T: LazyDeserialize> Serialize for Eager<T>
+where
+    T::Deserialized: Serialize,
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<T: Deserialize + Serialize, const MAX_LENGTH: usize> Serialize
+    for BoundedLinkedList<T, MAX_LENGTH>
+{
+    fn serialize<S: Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[derive(Deserialize, serde::Serialize)]
+    struct Table {
+        id: u16,
+        #[serde(serialize_with = "serialize_array")]
+        cells: [u8; 40],
+        data: Ptr<u32>,
+        next: Option<Ptr<Table>>,
+    }
+
+    #[test]
+    fn round_trips_arrays_and_pointers_through_json() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(Table::num_bytes::<X86_64>());
+        let cells: Vec<u8> = (0..40).collect();
+        memory.write_bytes(address, &7_u16.to_le_bytes());
+        memory.write_bytes(address + 2, &cells);
+        memory.write_ptr(address + 48, Address::new(0x2000));
+
+        let table = Table::deserialize(&mut memory, address).unwrap();
+        let json = to_json(&table).unwrap();
+        assert!(json.contains('\n'), "{json}");
+        assert_eq!(
+            serde_json::from_str::<Value>(&json).unwrap(),
+            json!({
+                "id": 7,
+                "cells": cells,
+                "data": "0x2000",
+                "next": null,
+            })
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::memory::{Address, Architecture};
+
+use super::Deserialize;
+
+/// The location and shape of a single field within a struct, as computed by
+/// the [`Deserialize`] derive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldLayout {
+    /// The field's name, or its index for tuple structs.
+    pub name: &'static str,
+
+    /// The offset of the field in bytes from the start of the struct.
+    pub offset: usize,
+
+    /// The number of bytes occupied by the field.
+    pub num_bytes: usize,
+
+    /// The required alignment of the field.
+    pub alignment: usize,
+
+    /// The field's type as written in the struct definition.
+    pub type_name: &'static str,
+}
+
+impl FieldLayout {
+    /// Assigns offsets to `fields` as if they were laid out one after another
+    /// in order, with each field aligned to its required alignment.
+    ///
+    /// Any offsets already present in `fields` are ignored.
+    #[must_use]
+    pub const fn sequential<const N: usize>(
+        mut fields: [FieldLayout; N],
+    ) -> [FieldLayout; N] {
+        let mut next_addr = Address::new(0);
+        let mut i = 0;
+        while i < N {
+            next_addr = next_addr.align_forward(fields[i].alignment);
+            fields[i].offset = next_addr.raw();
+            next_addr = next_addr.add_const(fields[i].num_bytes);
+            i += 1;
+        }
+        fields
+    }
+}
+
+/// Trait for structs whose field layout can be inspected without reading
+/// memory.
+///
+/// This is implemented automatically for all structs that derive
+/// [`Deserialize`].
+pub trait StructLayout: Deserialize {
+    /// The name of the struct.
+    const NAME: &'static str;
+
+    /// The layout of each field, in declaration order, when the target has
+    /// architecture `A`.
+    fn fields<A: Architecture>() -> Vec<FieldLayout>;
+
+    /// Finds the layout of the field named `name` when the target has
+    /// architecture `A`, if it exists.
+    #[must_use]
+    fn field_layout<A: Architecture>(name: &str) -> Option<FieldLayout> {
+        Self::fields::<A>()
+            .into_iter()
+            .find(|field| field.name == name)
+    }
+}
+
+/// A human-readable table describing the layout of a struct.
+///
+/// Created by [`layout_table`].
+pub struct LayoutTable {
+    name: &'static str,
+    num_bytes: usize,
+    alignment: usize,
+    fields: Vec<FieldLayout>,
+}
+
+/// Creates a table describing the layout of `T` when the target has
+/// architecture `A` that can be printed with [`Display`](fmt::Display).
+#[must_use]
+pub fn layout_table<T: StructLayout, A: Architecture>() -> LayoutTable {
+    LayoutTable {
+        name: T::NAME,
+        num_bytes: T::num_bytes::<A>(),
+        alignment: T::alignment::<A>(),
+        fields: T::fields::<A>(),
+    }
+}
+
+impl fmt::Display for LayoutTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} (size 0x{:x}, alignment {})",
+            self.name, self.num_bytes, self.alignment,
+        )?;
+        writeln!(f, "{:>8} {:>8} {:>5}  field", "offset", "size", "align")?;
+        for field in &self.fields {
+            writeln!(
+                f,
+                "{:>#8x} {:>#8x} {:>5}  {}: {}",
+                field.offset,
+                field.num_bytes,
+                field.alignment,
+                field.name,
+                field.type_name,
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
-use std::collections::LinkedList;
+use std::collections::{HashSet, LinkedList};
 
-use crate::memory::{Address, MemoryReader};
+use crate::memory::{Address, Architecture, MemoryReader};
 
-use super::ptr::{PTR_ALIGNMENT, PTR_NUM_BYTES};
-use super::Deserialize;
 use super::Error as DeserializeError;
+use super::{Deserialize, PathSegment};
+
+/// The maximum number of nodes read when deserializing a [`LinkedList`].
+pub const DEFAULT_MAX_LINKED_LIST_LENGTH: usize = 64 * 1024;
 
 #[derive(Deserialize)]
 struct Node<T: Deserialize> {
@@ -12,32 +14,168 @@ struct Node<T: Deserialize> {
     next: usize,
 }
 
+fn read_linked_list<T: Deserialize, M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+    max_length: usize,
+) -> Result<LinkedList<T>, DeserializeError> {
+    let mut result = LinkedList::<T>::new();
+    let mut visited = HashSet::<Address>::new();
+
+    let mut next_node_addr = address;
+    while next_node_addr.raw() != 0 {
+        if !visited.insert(next_node_addr) {
+            return Err(DeserializeError::LinkedListCycleError(next_node_addr));
+        }
+        if result.len() >= max_length {
+            return Err(DeserializeError::LinkedListLengthError(
+                next_node_addr,
+                max_length,
+            ));
+        }
+
+        let node = Node::<T>::deserialize(reader, next_node_addr).map_err(
+            |error| {
+                error.within(PathSegment::Index {
+                    index: result.len(),
+                    address: next_node_addr,
+                })
+            },
+        )?;
+        result.push_back(node.value);
+        next_node_addr = Address::new(node.next);
+    }
+
+    Ok(result)
+}
+
 impl<T: Deserialize> Deserialize for LinkedList<T> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        read_linked_list(reader, address, DEFAULT_MAX_LINKED_LIST_LENGTH)
+    }
+}
+
+/// A linked list that may contain at most `MAX_LENGTH` nodes.
+///
+/// This deserializes identically to [`LinkedList`] but with a custom length
+/// limit in place of [`DEFAULT_MAX_LINKED_LIST_LENGTH`].
+pub struct BoundedLinkedList<T: Deserialize, const MAX_LENGTH: usize> {
+    pub value: LinkedList<T>,
+}
+
+impl<T: Deserialize, const MAX_LENGTH: usize> Deserialize
+    for BoundedLinkedList<T, MAX_LENGTH>
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
         address: Address,
     ) -> Result<Self, DeserializeError> {
-        // TODO: check for loops
-
-        let mut result = LinkedList::<T>::new();
-
-        let mut next_node_addr = address;
-        while next_node_addr.raw() != 0 {
-            let node = Node::<T>::deserialize(reader, next_node_addr).map_err(
-                |error| {
-                    DeserializeError::WithContext(
-                        Box::new(error),
-                        format!("LinkedList.{}", result.len()),
-                    )
-                },
-            )?;
-            result.push_back(node.value);
-            next_node_addr = Address::new(node.next);
+        Ok(Self {
+            value: read_linked_list(reader, address, MAX_LENGTH)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    // Writes a list of nodes holding `values` and returns the node addresses.
+    fn write_nodes(memory: &mut TestMemory, values: &[u64]) -> Vec<Address> {
+        let nodes: Vec<_> = values.iter().map(|_| memory.alloc(16)).collect();
+        for (i, (&node, value)) in nodes.iter().zip(values).enumerate() {
+            memory.write_bytes(node, &value.to_le_bytes());
+            if let Some(&next) = nodes.get(i + 1) {
+                memory.write_ptr(node + 8, next);
+            }
         }
+        nodes
+    }
+
+    #[test]
+    fn reads_nodes_until_null() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let nodes = write_nodes(&mut memory, &[1, 2, 3]);
+
+        let list =
+            LinkedList::<u64>::deserialize(&mut memory, nodes[0]).unwrap();
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn reports_cycle_at_first_revisited_node() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let nodes = write_nodes(&mut memory, &[1, 2, 3]);
+        memory.write_ptr(nodes[2] + 8, nodes[1]);
+
+        let error =
+            LinkedList::<u64>::deserialize(&mut memory, nodes[0]).unwrap_err();
+        assert!(matches!(
+            error,
+            DeserializeError::LinkedListCycleError(address)
+                if address == nodes[1]
+        ));
+    }
+
+    #[test]
+    fn reports_first_node_beyond_maximum_length() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let nodes = write_nodes(&mut memory, &[1, 2, 3]);
+
+        let error =
+            BoundedLinkedList::<u64, 2>::deserialize(&mut memory, nodes[0])
+                .err()
+                .unwrap();
+        assert!(matches!(
+            error,
+            DeserializeError::LinkedListLengthError(address, 2)
+                if address == nodes[2]
+        ));
+
+        let list =
+            BoundedLinkedList::<u64, 3>::deserialize(&mut memory, nodes[0])
+                .ok()
+                .unwrap();
+        assert_eq!(list.value.len(), 3);
+    }
+
+    #[test]
+    fn reports_index_of_unreadable_node() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let nodes = write_nodes(&mut memory, &[1, 2]);
+        memory.write_ptr(nodes[1] + 8, Address::new(0x9000));
 
-        Ok(result)
+        let error =
+            LinkedList::<u64>::deserialize(&mut memory, nodes[0]).unwrap_err();
+        assert!(matches!(error.root_cause(), DeserializeError::IoError(_)));
+        assert_eq!(
+            error.path().unwrap().segments().next(),
+            Some(&PathSegment::Index {
+                index: 2,
+                address: Address::new(0x9000),
+            })
+        );
     }
 }
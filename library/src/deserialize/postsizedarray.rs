@@ -1,18 +1,76 @@
-use crate::memory::{Address, MemoryReader};
+use std::fmt;
+
+use crate::memory::{Address, Architecture, MemoryReader};
 
 use super::Error as DeserializeError;
-use super::{ArrayPtr, Deserialize, LazyDeserialize};
+use super::{ArrayPtr, Deserialize, LazyDeserialize, PathSegment};
 
 #[derive(Deserialize)]
+struct RawPostSizedArray<T: Deserialize, U: Deserialize> {
+    array_ptr: Option<ArrayPtr<T>>,
+    size: U,
+}
+
 pub struct PostSizedArray<T: Deserialize, U = usize>
 where
     U: Copy + Deserialize + TryInto<usize>,
     DeserializeError: From<<U as TryInto<usize>>::Error>,
 {
+    address: Address,
     array_ptr: Option<ArrayPtr<T>>,
     size: U,
 }
 
+impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>>
+    PostSizedArray<T, U>
+where
+    DeserializeError: From<<U as TryInto<usize>>::Error>,
+{
+    /// Gets the address this array's pointer and size were deserialized from.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gets the pointer to the first element of the array, if it is non-null.
+    #[must_use]
+    pub fn array_ptr(&self) -> Option<&ArrayPtr<T>> {
+        self.array_ptr.as_ref()
+    }
+
+    /// Gets the number of elements in the array, as stored in memory.
+    #[must_use]
+    pub fn size(&self) -> U {
+        self.size
+    }
+}
+
+impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>> Deserialize
+    for PostSizedArray<T, U>
+where
+    DeserializeError: From<<U as TryInto<usize>>::Error>,
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        RawPostSizedArray::<T, U>::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        RawPostSizedArray::<T, U>::alignment::<A>()
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        let raw = RawPostSizedArray::<T, U>::deserialize(reader, address)?;
+        Ok(Self {
+            address,
+            array_ptr: raw.array_ptr,
+            size: raw.size,
+        })
+    }
+}
+
 impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>> LazyDeserialize
     for PostSizedArray<T, U>
 where
@@ -33,11 +91,30 @@ where
         if let Some(ref ptr) = self.array_ptr {
             ptr.deref(reader, size)
         } else {
-            // TODO: capture the actual address
-            Err(DeserializeError::WithContext(
-                Box::new(DeserializeError::NullPtrError(Address::new(0))),
-                format!("Null arrayptr in post-sized array with size {size}"),
+            // The array pointer is the first field, so it is located at the
+            // start of the struct.
+            Err(DeserializeError::NullPtrError(self.address).within(
+                PathSegment::Field {
+                    struct_name: "PostSizedArray",
+                    field_name: "array_ptr",
+                    address: self.address,
+                },
             ))
         }
     }
 }
+
+impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>> fmt::Debug
+    for PostSizedArray<T, U>
+where
+    U: fmt::Debug,
+    DeserializeError: From<<U as TryInto<usize>>::Error>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PostSizedArray")
+            .field("address", &format_args!("{}", self.address))
+            .field("array_ptr", &self.array_ptr)
+            .field("size", &self.size)
+            .finish()
+    }
+}
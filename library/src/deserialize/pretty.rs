@@ -0,0 +1,396 @@
+use std::collections::{HashMap, LinkedList};
+use std::fmt;
+
+use crate::memory::Address;
+
+use super::Error as DeserializeError;
+use super::{
+    ArrayPtr, BoundedLinkedList, Consistent, Deserialize, Eager,
+    LazyDeserialize, PostSizedArray, Ptr, RelPtr, TaggedPtr, Version,
+    ZeroLengthArray,
+};
+
+pub use grub_split_macros::PrettyPrint;
+
+/// The depth to which values are printed by their [`Debug`](fmt::Debug)
+/// implementations when they derive [`PrettyPrint`].
+pub const DEFAULT_MAX_DEPTH: usize = 4;
+
+/// Trait for deserialized values that can be printed for debugging with a
+/// limit on how deeply nested values are expanded.
+///
+/// Lazy pointers are printed as addresses and are never dereferenced, so
+/// printing cannot loop even if the structures in memory contain cycles.
+pub trait PrettyPrint {
+    /// Writes this value to `f`, expanding at most `depth` levels of nested
+    /// values. Values nested more deeply are elided.
+    ///
+    /// Nested values should be written using [`Pretty`] so that `f`'s flags
+    /// (e.g. `{:#?}`) are respected.
+    fn pretty_print(&self, f: &mut fmt::Formatter, depth: usize)
+        -> fmt::Result;
+
+    /// Creates a value that prints this value to depth `depth` using
+    /// [`Debug`](fmt::Debug).
+    fn pretty(&self, depth: usize) -> Pretty<'_, Self> {
+        Pretty { value: self, depth }
+    }
+}
+
+/// Adapts a [`PrettyPrint`] value to [`Debug`](fmt::Debug) with a fixed
+/// remaining depth.
+pub struct Pretty<'a, T: PrettyPrint + ?Sized> {
+    value: &'a T,
+    depth: usize,
+}
+
+impl<'a, T: PrettyPrint + ?Sized> Pretty<'a, T> {
+    /// Creates a value that prints `value` to depth `depth`.
+    #[must_use]
+    pub fn new(value: &'a T, depth: usize) -> Self {
+        Self { value, depth }
+    }
+}
+
+impl<'a, T: PrettyPrint + ?Sized> fmt::Debug for Pretty<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.pretty_print(f, self.depth)
+    }
+}
+
+macro_rules! pretty_print_debug_impl {
+    ($T:ty) => {
+        impl PrettyPrint for $T {
+            fn pretty_print(
+                &self,
+                f: &mut fmt::Formatter,
+                _depth: usize,
+            ) -> fmt::Result {
+                fmt::Debug::fmt(self, f)
+            }
+        }
+    };
+}
+
+pretty_print_debug_impl!(u8);
+pretty_print_debug_impl!(i8);
+pretty_print_debug_impl!(u16);
+pretty_print_debug_impl!(i16);
+pretty_print_debug_impl!(u32);
+pretty_print_debug_impl!(i32);
+pretty_print_debug_impl!(u64);
+pretty_print_debug_impl!(i64);
+pretty_print_debug_impl!(f32);
+pretty_print_debug_impl!(f64);
+pretty_print_debug_impl!(usize);
+pretty_print_debug_impl!(isize);
+pretty_print_debug_impl!(bool);
+pretty_print_debug_impl!(str);
+pretty_print_debug_impl!(String);
+
+impl PrettyPrint for Address {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl PrettyPrint for Version {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+impl<T> PrettyPrint for Ptr<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> PrettyPrint for TaggedPtr<T, MASK> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> PrettyPrint for RelPtr<T, W> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize> PrettyPrint for ArrayPtr<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize> PrettyPrint for ZeroLengthArray<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize, U: Copy + Deserialize + TryInto<usize>> PrettyPrint
+    for PostSizedArray<T, U>
+where
+    U: fmt::Debug,
+    DeserializeError: From<<U as TryInto<usize>>::Error>,
+{
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<T: Deserialize + PrettyPrint, const MAX_ATTEMPTS: usize> PrettyPrint
+    for Consistent<T, MAX_ATTEMPTS>
+{
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        self.value.pretty_print(f, depth)
+    }
+}
+
+impl<T: LazyDeserialize> PrettyPrint for Eager<T>
+where
+    T::Deserialized: PrettyPrint,
+{
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        self.value.pretty_print(f, depth)
+    }
+}
+
+impl<T: PrettyPrint + ?Sized> PrettyPrint for &T {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        (**self).pretty_print(f, depth)
+    }
+}
+
+impl<T: PrettyPrint> PrettyPrint for Option<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        match self {
+            None => write!(f, "None"),
+            Some(value) => {
+                f.debug_tuple("Some").field(&value.pretty(depth)).finish()
+            }
+        }
+    }
+}
+
+// Writes the elements of a sequence, eliding them if no depth remains.
+fn pretty_print_list<'a, T: PrettyPrint + 'a>(
+    f: &mut fmt::Formatter,
+    depth: usize,
+    elements: impl ExactSizeIterator<Item = &'a T>,
+) -> fmt::Result {
+    if depth == 0 {
+        return write!(f, "[.. {} elements]", elements.len());
+    }
+    f.debug_list()
+        .entries(elements.map(|element| element.pretty(depth - 1)))
+        .finish()
+}
+
+impl<T: PrettyPrint, const N: usize> PrettyPrint for [T; N] {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        pretty_print_list(f, depth, self.iter())
+    }
+}
+
+impl<T: PrettyPrint> PrettyPrint for Vec<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        pretty_print_list(f, depth, self.iter())
+    }
+}
+
+impl<T: PrettyPrint> PrettyPrint for LinkedList<T> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        pretty_print_list(f, depth, self.iter())
+    }
+}
+
+impl<T: Deserialize + PrettyPrint, const MAX_LENGTH: usize> PrettyPrint
+    for BoundedLinkedList<T, MAX_LENGTH>
+{
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        self.value.pretty_print(f, depth)
+    }
+}
+
+impl<K: PrettyPrint, V: PrettyPrint, S> PrettyPrint for HashMap<K, V, S> {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        if depth == 0 {
+            return write!(f, "{{.. {} entries}}", self.len());
+        }
+        f.debug_map()
+            .entries(
+                self.iter()
+                    .map(|(k, v)| (k.pretty(depth - 1), v.pretty(depth - 1))),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[derive(PrettyPrint)]
+    struct Inner {
+        id: u32,
+        pointer: Ptr<u32>,
+    }
+
+    #[derive(PrettyPrint)]
+    struct Outer {
+        name: String,
+        inner: Inner,
+        values: Vec<u8>,
+        address: Option<Address>,
+    }
+
+    #[derive(PrettyPrint)]
+    struct Pair(u8, Address);
+
+    #[derive(PrettyPrint)]
+    struct Unit;
+
+    fn outer() -> Outer {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(8);
+        memory.write_ptr(slot, Address::new(0x2000));
+        Outer {
+            name: "Player".to_string(),
+            inner: Inner {
+                id: 7,
+                pointer: Ptr::deserialize(&mut memory, slot).unwrap(),
+            },
+            values: vec![1, 2],
+            address: Some(Address::new(0x3000)),
+        }
+    }
+
+    #[test]
+    fn derived_debug_prints_fields_to_default_depth() {
+        assert_eq!(
+            format!("{:?}", outer()),
+            "Outer { name: \"Player\", inner: Inner { id: 7, pointer: \
+             Ptr(0x1000 -> 0x2000) }, values: [1, 2], address: Some(0x3000) }"
+        );
+        assert_eq!(
+            format!("{:?}", Pair(1, Address::new(0xff))),
+            "Pair(1, 0xff)"
+        );
+        assert_eq!(format!("{Unit:?}"), "Unit");
+    }
+
+    #[test]
+    fn elides_values_nested_beyond_depth() {
+        let outer = outer();
+        assert_eq!(format!("{:?}", outer.pretty(0)), "Outer { .. }");
+        assert_eq!(
+            format!("{:?}", outer.pretty(1)),
+            "Outer { name: \"Player\", inner: Inner { .. }, values: [.. 2 \
+             elements], address: Some(0x3000) }"
+        );
+        assert_eq!(
+            format!("{:?}", Pair(1, Address::new(0xff)).pretty(0)),
+            "Pair(..)"
+        );
+        assert_eq!(format!("{:?}", Unit.pretty(0)), "Unit");
+
+        let map = HashMap::from([(1_u8, 2_u8), (3, 4)]);
+        assert_eq!(format!("{:?}", map.pretty(0)), "{.. 2 entries}");
+        let map = HashMap::from([(1_u8, outer)]);
+        assert_eq!(format!("{:?}", map.pretty(1)), "{1: Outer { .. }}");
+    }
+
+    #[test]
+    fn prints_addresses_in_hex() {
+        let address = Address::new(0x1f);
+        assert_eq!(format!("{:?}", address.pretty(0)), "0x1f");
+        assert_eq!(format!("{:?}", Some(address).pretty(0)), "Some(0x1f)");
+        assert_eq!(format!("{:?}", Option::<Address>::None.pretty(1)), "None");
+        assert_eq!(
+            format!("{:?}", [address, Address::new(0x20)].pretty(1)),
+            "[0x1f, 0x20]"
+        );
+    }
+
+    #[test]
+    fn respects_alternate_flag() {
+        assert_eq!(
+            format!("{:#?}", Pair(1, Address::new(0xff))),
+            "Pair(\n    1,\n    0xff,\n)"
+        );
+    }
+}
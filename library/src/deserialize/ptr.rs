@@ -1,35 +1,43 @@
+use std::fmt;
 use std::marker::PhantomData;
 
-use crate::memory::{Address, AddressRange, MemoryReader};
+use crate::memory::{Address, Architecture, MemoryReader};
 
+use super::deserializetrait::read_pointer_sized;
 use super::Deserialize;
 use super::Error as DeserializeError;
-use super::LazyDeserialize;
-
-pub const PTR_NUM_BYTES: usize = std::mem::size_of::<usize>();
-pub const PTR_ALIGNMENT: usize = std::mem::align_of::<usize>();
+use super::{LazyDeserialize, PathSegment};
 
 impl Deserialize for Option<Address> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
         address: Address,
     ) -> Result<Self, DeserializeError> {
-        let range = AddressRange::<PTR_NUM_BYTES> { start: address };
-        let addr_raw = usize::from_ne_bytes(reader.read(range)?);
+        let addr_raw = read_pointer_sized(reader, address)?;
         Ok(if addr_raw == 0 {
             None
         } else {
-            Some(Address::new(addr_raw))
+            Some(Address::new(addr_raw.try_into()?))
         })
     }
 }
 
 impl Deserialize for Address {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -42,12 +50,46 @@ impl Deserialize for Address {
     }
 }
 
-#[derive(Debug)]
-pub struct Ptr<T: Deserialize> {
+/// A non-null pointer to a `T` in another process's memory.
+///
+/// Pointers to any type can be read, but only pointers to types that implement
+/// [`Deserialize`] can be dereferenced with [`deref`](LazyDeserialize::deref).
+/// Types whose layout depends on more than the architecture are read from the
+/// pointer's [`address`](Ptr::address) with their own functions instead.
+pub struct Ptr<T> {
+    slot: Address,
     address: Address,
     deref_type: PhantomData<T>,
 }
 
+impl<T> Ptr<T> {
+    /// Gets the address this pointer points to.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gets the address of the pointer itself.
+    #[must_use]
+    pub fn slot(&self) -> Address {
+        self.slot
+    }
+}
+
+impl<T> Clone for Ptr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Ptr<T> {}
+
+impl<T> fmt::Debug for Ptr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Ptr({} -> {})", self.slot, self.address)
+    }
+}
+
 impl<T: Deserialize> LazyDeserialize for Ptr<T> {
     type Deserialized = T;
 
@@ -55,13 +97,23 @@ impl<T: Deserialize> LazyDeserialize for Ptr<T> {
         &self,
         reader: &mut M,
     ) -> Result<Self::Deserialized, DeserializeError> {
-        T::deserialize(reader, self.address)
+        T::deserialize(reader, self.address).map_err(|error| {
+            error.within(PathSegment::Deref {
+                from: self.slot,
+                to: self.address,
+            })
+        })
     }
 }
 
-impl<T: Deserialize> Deserialize for Option<Ptr<T>> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+impl<T> Deserialize for Option<Ptr<T>> {
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -69,6 +121,7 @@ impl<T: Deserialize> Deserialize for Option<Ptr<T>> {
     ) -> Result<Self, DeserializeError> {
         Ok(Option::<Address>::deserialize(reader, address)?.map(
             |pointed_addr| Ptr {
+                slot: address,
                 address: pointed_addr,
                 deref_type: PhantomData,
             },
@@ -76,9 +129,14 @@ impl<T: Deserialize> Deserialize for Option<Ptr<T>> {
     }
 }
 
-impl<T: Deserialize> Deserialize for Ptr<T> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+impl<T> Deserialize for Ptr<T> {
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
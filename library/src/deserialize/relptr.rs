@@ -0,0 +1,181 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::memory::{Address, Architecture, MemoryReader};
+
+use super::Deserialize;
+use super::Error as DeserializeError;
+use super::{LazyDeserialize, PathSegment};
+
+/// A pointer stored as a signed offset of type `W` from the address of the
+/// pointer itself.
+///
+/// The pointer is null if its offset is zero.
+pub struct RelPtr<T: Deserialize, W: Deserialize + Into<i64> = i32> {
+    slot: Address,
+    address: Address,
+    deref_type: PhantomData<(T, W)>,
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> RelPtr<T, W> {
+    /// Gets the address this pointer points to.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gets the address of the pointer itself, which its offset is relative
+    /// to.
+    #[must_use]
+    pub fn slot(&self) -> Address {
+        self.slot
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> fmt::Debug for RelPtr<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RelPtr({} -> {})", self.slot, self.address)
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> LazyDeserialize
+    for RelPtr<T, W>
+{
+    type Deserialized = T;
+
+    fn deref<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Self::Deserialized, DeserializeError> {
+        T::deserialize(reader, self.address).map_err(|error| {
+            error.within(PathSegment::Deref {
+                from: self.slot,
+                to: self.address,
+            })
+        })
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> Deserialize
+    for Option<RelPtr<T, W>>
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        W::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        W::alignment::<A>()
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        let offset: i64 = W::deserialize(reader, address)?.into();
+        if offset == 0 {
+            return Ok(None);
+        }
+
+        let pointed_addr = address
+            .raw()
+            .checked_add_signed(offset.try_into()?)
+            .ok_or(DeserializeError::AddressOverflowError(address))?;
+        Ok(Some(RelPtr {
+            slot: address,
+            address: Address::new(pointed_addr),
+            deref_type: PhantomData,
+        }))
+    }
+}
+
+impl<T: Deserialize, W: Deserialize + Into<i64>> Deserialize for RelPtr<T, W> {
+    fn num_bytes<A: Architecture>() -> usize {
+        W::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        W::alignment::<A>()
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        match Option::<RelPtr<T, W>>::deserialize(reader, address)? {
+            Some(ptr) => Ok(ptr),
+            None => Err(DeserializeError::NullPtrError(address)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[test]
+    fn points_relative_to_slot() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let before = memory.alloc(8);
+        memory.write_bytes(before, &1_u64.to_le_bytes());
+        let slot = memory.alloc(8);
+        memory.write_bytes(slot, &(-8_i32).to_le_bytes());
+        memory.write_bytes(slot + 4, &4_i32.to_le_bytes());
+        let after = memory.alloc(8);
+        memory.write_bytes(after, &2_u64.to_le_bytes());
+
+        let ptr = RelPtr::<u64>::deserialize(&mut memory, slot).ok().unwrap();
+        assert_eq!(ptr.slot(), slot);
+        assert_eq!(ptr.address(), before);
+        assert_eq!(ptr.deref(&mut memory).unwrap(), 1);
+
+        let ptr = RelPtr::<u64>::deserialize(&mut memory, slot + 4)
+            .ok()
+            .unwrap();
+        assert_eq!(ptr.address(), after);
+        assert_eq!(ptr.deref(&mut memory).unwrap(), 2);
+    }
+
+    #[test]
+    fn reads_offset_of_given_width() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(4);
+        memory.write_bytes(slot, &0x10_i16.to_le_bytes());
+        memory.write_bytes(slot + 2, &(-1_i16).to_le_bytes());
+
+        assert_eq!(RelPtr::<u8, i16>::num_bytes::<X86_64>(), 2);
+        let ptr = RelPtr::<u8, i16>::deserialize(&mut memory, slot)
+            .ok()
+            .unwrap();
+        assert_eq!(ptr.address(), slot + 0x10);
+    }
+
+    #[test]
+    fn is_null_when_offset_is_zero() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(4);
+
+        let ptr =
+            Option::<RelPtr<u64>>::deserialize(&mut memory, slot).unwrap();
+        assert!(ptr.is_none());
+        assert!(matches!(
+            RelPtr::<u64>::deserialize(&mut memory, slot),
+            Err(DeserializeError::NullPtrError(address)) if address == slot
+        ));
+    }
+
+    #[test]
+    fn reports_offset_below_zero_address() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(4);
+        memory.write_bytes(slot, &(-0x2000_i32).to_le_bytes());
+
+        assert!(matches!(
+            RelPtr::<u64>::deserialize(&mut memory, slot),
+            Err(DeserializeError::AddressOverflowError(address))
+                if address == slot
+        ));
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::memory::{Address, MemoryReader};
+
+use super::Deserialize;
+use super::Error as DeserializeError;
+use super::Ptr;
+
+/// A reference to a `T` in another process's memory that is not read until
+/// needed.
+///
+/// Structs that derive [`Deserialize`] with `#[deserialize(remote)]` get an
+/// accessor on `RemoteRef<Self>` for each named field that reads only that
+/// field, so that reading one field does not require reading the whole struct.
+pub struct RemoteRef<T> {
+    address: Address,
+    deref_type: PhantomData<T>,
+}
+
+impl<T> RemoteRef<T> {
+    /// Creates a reference to the `T` starting at `address`.
+    #[must_use]
+    pub fn new(address: Address) -> Self {
+        Self {
+            address,
+            deref_type: PhantomData,
+        }
+    }
+
+    /// Gets the address of the referenced value.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+}
+
+impl<T: Deserialize> RemoteRef<T> {
+    /// Reads the entire referenced value using `reader`.
+    ///
+    /// Returns an [`Error`](super::Error) if deserialization fails.
+    pub fn read<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<T, DeserializeError> {
+        T::deserialize(reader, self.address)
+    }
+}
+
+impl<T> Clone for RemoteRef<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RemoteRef<T> {}
+
+impl<T> From<&Ptr<T>> for RemoteRef<T> {
+    fn from(ptr: &Ptr<T>) -> Self {
+        Self::new(ptr.address())
+    }
+}
+
+impl<T> fmt::Debug for RemoteRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RemoteRef({})", self.address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deserialize::{LazyDeserialize, PathSegment};
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[deserialize(remote)]
+    struct Header {
+        kind: u16,
+        length: u32,
+        target: Ptr<u64>,
+    }
+
+    #[test]
+    fn reads_each_field_at_its_offset() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let target = memory.alloc(8);
+        memory.write_bytes(target, &42_u64.to_le_bytes());
+        let address = memory.alloc(Header::num_bytes::<X86_64>());
+        memory.write_bytes(address, &7_u16.to_le_bytes());
+        memory.write_bytes(address + 4, &1234_u32.to_le_bytes());
+        memory.write_ptr(address + 8, target);
+
+        let header = RemoteRef::<Header>::new(address);
+        assert_eq!(header.length(&mut memory).unwrap(), 1234);
+        assert_eq!(memory.num_reads(), 1);
+        assert_eq!(header.kind(&mut memory).unwrap(), 7);
+        let ptr = header.target(&mut memory).unwrap();
+        assert_eq!(ptr.deref(&mut memory).unwrap(), 42);
+
+        let header = header.read(&mut memory).unwrap();
+        assert_eq!((header.kind, header.length), (7, 1234));
+        assert_eq!(header.target.address(), target);
+    }
+
+    #[test]
+    fn reads_field_without_reading_rest_of_struct() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address + 4, &1234_u32.to_le_bytes());
+
+        let header = RemoteRef::<Header>::new(address);
+        assert_eq!(header.length(&mut memory).unwrap(), 1234);
+
+        let error = header.target(&mut memory).unwrap_err();
+        assert_eq!(
+            error.path().unwrap().segments().next(),
+            Some(&PathSegment::Field {
+                struct_name: "Header",
+                field_name: "target",
+                address: address + 8,
+            })
+        );
+    }
+}
@@ -1,14 +1,13 @@
 use std::option::Option;
 
-use crate::memory::{Address, AddressRange, MemoryReader};
+use crate::memory::{Address, AddressRange, Architecture, MemoryReader};
 
-use super::ptr::{PTR_ALIGNMENT, PTR_NUM_BYTES};
-use super::Deserialize;
 use super::Error as DeserializeError;
+use super::{Deserialize, PathSegment};
 
 const MAX_STRING_LENGTH: usize = 1024 * 1024;
 
-fn read_c_string<M: MemoryReader>(
+pub(crate) fn read_c_string<M: MemoryReader>(
     reader: &mut M,
     address: Address,
 ) -> Result<String, DeserializeError> {
@@ -36,8 +35,13 @@ fn read_c_string<M: MemoryReader>(
 }
 
 impl Deserialize for Option<String> {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -45,14 +49,26 @@ impl Deserialize for Option<String> {
     ) -> Result<Self, DeserializeError> {
         match Option::<Address>::deserialize(reader, address)? {
             None => Ok(None),
-            Some(pointed_addr) => read_c_string(reader, pointed_addr).map(Some),
+            Some(pointed_addr) => read_c_string(reader, pointed_addr)
+                .map(Some)
+                .map_err(|error| {
+                    error.within(PathSegment::Deref {
+                        from: address,
+                        to: pointed_addr,
+                    })
+                }),
         }
     }
 }
 
 impl Deserialize for String {
-    const NUM_BYTES: usize = PTR_NUM_BYTES;
-    const ALIGNMENT: usize = PTR_ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
 
     fn deserialize<M: MemoryReader>(
         reader: &mut M,
@@ -0,0 +1,196 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::memory::{Address, Architecture, MemoryReader};
+
+use super::Deserialize;
+use super::Error as DeserializeError;
+use super::{LazyDeserialize, PathSegment};
+
+/// A pointer whose bits selected by `MASK` hold a tag rather than part of the
+/// address, such as the low bits of an aligned pointer.
+///
+/// The pointer is null if its address is zero once the tag is removed.
+pub struct TaggedPtr<T: Deserialize, const MASK: usize> {
+    slot: Address,
+    address: Address,
+    tag: usize,
+    deref_type: PhantomData<T>,
+}
+
+impl<T: Deserialize, const MASK: usize> TaggedPtr<T, MASK> {
+    /// Gets the address this pointer points to, with the tag removed.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gets the address of the pointer itself.
+    #[must_use]
+    pub fn slot(&self) -> Address {
+        self.slot
+    }
+
+    /// Gets the bits of the pointer selected by `MASK`.
+    #[must_use]
+    pub fn tag(&self) -> usize {
+        self.tag
+    }
+
+    /// Returns whether any of the bits in `bits` are set in the tag.
+    #[must_use]
+    pub fn has_tag(&self, bits: usize) -> bool {
+        self.tag & bits != 0
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> fmt::Debug for TaggedPtr<T, MASK> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "TaggedPtr({} -> {}, tag 0x{:x})",
+            self.slot, self.address, self.tag
+        )
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> LazyDeserialize for TaggedPtr<T, MASK> {
+    type Deserialized = T;
+
+    fn deref<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Self::Deserialized, DeserializeError> {
+        T::deserialize(reader, self.address).map_err(|error| {
+            error.within(PathSegment::Deref {
+                from: self.slot,
+                to: self.address,
+            })
+        })
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> Deserialize
+    for Option<TaggedPtr<T, MASK>>
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        let raw = usize::deserialize(reader, address)?;
+        let pointed_addr = raw & !MASK;
+        Ok(if pointed_addr == 0 {
+            None
+        } else {
+            Some(TaggedPtr {
+                slot: address,
+                address: Address::new(pointed_addr),
+                tag: raw & MASK,
+                deref_type: PhantomData,
+            })
+        })
+    }
+}
+
+impl<T: Deserialize, const MASK: usize> Deserialize for TaggedPtr<T, MASK> {
+    fn num_bytes<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        A::POINTER_WIDTH
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        match Option::<TaggedPtr<T, MASK>>::deserialize(reader, address)? {
+            Some(ptr) => Ok(ptr),
+            None => Err(DeserializeError::NullPtrError(address)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{X86, X86_64};
+
+    use super::*;
+
+    #[test]
+    fn separates_tag_from_address() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let target = memory.alloc(8);
+        memory.write_bytes(target, &42_u64.to_le_bytes());
+        let slot = memory.alloc(8);
+        memory.write_ptr(slot, target + 0b101);
+
+        let ptr = TaggedPtr::<u64, 0b111>::deserialize(&mut memory, slot)
+            .ok()
+            .unwrap();
+        assert_eq!(ptr.slot(), slot);
+        assert_eq!(ptr.address(), target);
+        assert_eq!(ptr.tag(), 0b101);
+        assert!(ptr.has_tag(0b100));
+        assert!(!ptr.has_tag(0b010));
+        assert_eq!(ptr.deref(&mut memory).unwrap(), 42);
+    }
+
+    #[test]
+    fn reads_pointer_of_architecture_width() {
+        let mut memory = TestMemory::<X86>::new(Address::new(0x1000));
+        let slot = memory.alloc(8);
+        memory.write_bytes(slot, &0x0000_2003_u32.to_le_bytes());
+        memory.write_bytes(slot + 4, &u32::MAX.to_le_bytes());
+
+        let ptr = TaggedPtr::<u32, 0b11>::deserialize(&mut memory, slot)
+            .ok()
+            .unwrap();
+        assert_eq!(ptr.address(), Address::new(0x2000));
+        assert_eq!(ptr.tag(), 0b11);
+    }
+
+    #[test]
+    fn is_null_when_only_tag_is_set() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(8);
+        memory.write_bytes(slot, &1_u64.to_le_bytes());
+
+        let ptr = Option::<TaggedPtr<u64, 1>>::deserialize(&mut memory, slot)
+            .unwrap();
+        assert!(ptr.is_none());
+        assert!(matches!(
+            TaggedPtr::<u64, 1>::deserialize(&mut memory, slot),
+            Err(DeserializeError::NullPtrError(address)) if address == slot
+        ));
+    }
+
+    #[test]
+    fn reports_deref_in_error_path() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = memory.alloc(8);
+        memory.write_ptr(slot, Address::new(0x9001));
+
+        let ptr = TaggedPtr::<u64, 1>::deserialize(&mut memory, slot)
+            .ok()
+            .unwrap();
+        let error = ptr.deref(&mut memory).unwrap_err();
+        assert_eq!(
+            error.path().unwrap().segments().next(),
+            Some(&PathSegment::Deref {
+                from: slot,
+                to: Address::new(0x9000),
+            })
+        );
+    }
+}
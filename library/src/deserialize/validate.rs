@@ -0,0 +1,21 @@
+use crate::memory::Address;
+
+use super::Error as DeserializeError;
+
+/// Trait for types that can check their own invariants after being
+/// deserialized.
+///
+/// Memory in a running process can change while it is being read, so values
+/// that deserialize successfully may still be inconsistent. Validation lets
+/// such values be rejected early rather than causing confusing failures later.
+///
+/// Structs deriving [`Deserialize`](super::Deserialize) can opt into
+/// validation with `#[deserialize(validate)]`.
+pub trait Validate {
+    /// Checks that this value, which was deserialized from `address`, is in a
+    /// valid state.
+    ///
+    /// Returns an [`InvalidStateError`](super::Error::InvalidStateError) if it
+    /// is not.
+    fn validate(&self, address: Address) -> Result<(), DeserializeError>;
+}
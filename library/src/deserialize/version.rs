@@ -0,0 +1,125 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+const NUM_COMPONENTS: usize = 4;
+
+/// A version of the target process (e.g. a game or runtime version) that
+/// determines how structures are laid out in its memory.
+///
+/// Versions have up to four numeric components and are ordered
+/// lexicographically by component. Missing trailing components are treated as
+/// zero, so `1.4` and `1.4.0.0` are equal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Version([u16; NUM_COMPONENTS]);
+
+impl Version {
+    /// Constructs a new `Version` from its four components.
+    #[must_use]
+    pub const fn new(major: u16, minor: u16, patch: u16, build: u16) -> Self {
+        Self([major, minor, patch, build])
+    }
+
+    /// Gets the components of this version, from most to least significant.
+    #[must_use]
+    pub const fn components(self) -> [u16; NUM_COMPONENTS] {
+        self.0
+    }
+}
+
+/// An error that occurs while parsing a [`Version`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseVersionError {
+    /// The version had no components or more than four components.
+    InvalidComponentCount(usize),
+
+    /// A component was not a valid 16-bit unsigned integer.
+    InvalidComponent(ParseIntError),
+}
+
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidComponentCount(count) => write!(
+                f,
+                "Expected 1 to {NUM_COMPONENTS} version components but found \
+                 {count}"
+            ),
+            Self::InvalidComponent(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        if string.is_empty() {
+            return Err(ParseVersionError::InvalidComponentCount(0));
+        }
+        let parts: Vec<&str> = string.split('.').collect();
+        if parts.len() > NUM_COMPONENTS {
+            return Err(ParseVersionError::InvalidComponentCount(parts.len()));
+        }
+
+        let mut components = [0; NUM_COMPONENTS];
+        for (component, part) in components.iter_mut().zip(parts) {
+            *component =
+                part.parse().map_err(ParseVersionError::InvalidComponent)?;
+        }
+        Ok(Self(components))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [major, minor, patch, build] = self.0;
+        write!(f, "{major}.{minor}.{patch}.{build}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_missing_components_as_zero() {
+        assert_eq!("5.11".parse(), Ok(Version::new(5, 11, 0, 0)));
+        assert_eq!("6.12.0.199".parse(), Ok(Version::new(6, 12, 0, 199)));
+        assert_eq!(Version::new(1, 4, 0, 0), "1.4.0.0".parse().unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_component_counts() {
+        assert_eq!(
+            "".parse::<Version>(),
+            Err(ParseVersionError::InvalidComponentCount(0))
+        );
+        assert_eq!(
+            "1.2.3.4.5".parse::<Version>(),
+            Err(ParseVersionError::InvalidComponentCount(5))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_components() {
+        assert!(matches!(
+            "1..2".parse::<Version>(),
+            Err(ParseVersionError::InvalidComponent(_))
+        ));
+        assert!(matches!(
+            "1.65536".parse::<Version>(),
+            Err(ParseVersionError::InvalidComponent(_))
+        ));
+    }
+
+    #[test]
+    fn orders_by_component() {
+        assert!(Version::new(5, 11, 0, 0) < Version::new(6, 0, 0, 0));
+        assert!(Version::new(6, 0, 0, 0) < Version::new(6, 0, 0, 1));
+        assert_eq!(Version::new(6, 12, 0, 199).to_string(), "6.12.0.199");
+    }
+}
@@ -0,0 +1,128 @@
+use crate::memory::{Address, Architecture, MemoryReader};
+
+use super::Deserialize;
+use super::Error as DeserializeError;
+use super::Version;
+
+pub use grub_split_macros::VersionedDeserialize;
+
+/// Trait for types whose layout in memory depends on the version of the target
+/// process.
+///
+/// Unlike [`Deserialize`], the size and alignment of these types are only
+/// known once a [`Version`] has been selected as well as an architecture.
+/// Every [`Deserialize`] type is also `VersionedDeserialize` with a layout that
+/// ignores the version, so fixed-layout types can be freely mixed into
+/// versioned structs.
+pub trait VersionedDeserialize: Sized {
+    /// The number of bytes that are required for deserialization when the
+    /// target has version `version` and architecture `A`.
+    fn num_bytes<A: Architecture>(version: Version) -> usize;
+
+    /// The required alignment of the bytes when the target has version
+    /// `version` and architecture `A`. Must be a power of two.
+    fn alignment<A: Architecture>(version: Version) -> usize;
+
+    /// Attempts to deserialize an instance laid out according to `version`
+    /// and the reader's architecture from memory starting at `address` using
+    /// `reader`.
+    ///
+    /// Returns an [`Error`](super::Error) if deserialization fails.
+    fn deserialize_versioned<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        version: Version,
+    ) -> Result<Self, DeserializeError>;
+}
+
+impl<T: Deserialize> VersionedDeserialize for T {
+    fn num_bytes<A: Architecture>(_version: Version) -> usize {
+        <T as Deserialize>::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>(_version: Version) -> usize {
+        <T as Deserialize>::alignment::<A>()
+    }
+
+    fn deserialize_versioned<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        _version: Version,
+    ) -> Result<Self, DeserializeError> {
+        T::deserialize(reader, address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deserialize::RemoteRef;
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    const OLD: Version = Version::new(5, 11, 0, 0);
+    const NEW: Version = Version::new(6, 0, 0, 0);
+
+    #[derive(VersionedDeserialize)]
+    struct Single {
+        value: u32,
+    }
+
+    #[derive(VersionedDeserialize)]
+    #[versioned(remote)]
+    struct Pair {
+        first: u8,
+        #[versioned(since = "6.0")]
+        second: Option<u32>,
+    }
+
+    #[test]
+    fn reads_single_field_struct() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(4);
+        memory.write_bytes(address, &7u32.to_le_bytes());
+
+        let single =
+            Single::deserialize_versioned(&mut memory, address, OLD).unwrap();
+        assert_eq!(single.value, 7);
+        assert_eq!(Single::offset_of::<X86_64>(OLD, "value"), Some(0));
+        assert_eq!(Single::num_bytes::<X86_64>(OLD), 4);
+    }
+
+    #[test]
+    fn lays_out_fields_by_version() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &[3]);
+        memory.write_bytes(address + 4, &9u32.to_le_bytes());
+
+        let old =
+            Pair::deserialize_versioned(&mut memory, address, OLD).unwrap();
+        assert_eq!((old.first, old.second), (3, None));
+        let new =
+            Pair::deserialize_versioned(&mut memory, address, NEW).unwrap();
+        assert_eq!((new.first, new.second), (3, Some(9)));
+
+        assert_eq!(Pair::offset_of::<X86_64>(OLD, "second"), None);
+        assert_eq!(Pair::offset_of::<X86_64>(NEW, "second"), Some(4));
+        // One read for each struct.
+        assert_eq!(memory.num_reads(), 2);
+
+        assert_eq!(Pair::num_bytes::<X86_64>(OLD), 1);
+        assert_eq!(Pair::num_bytes::<X86_64>(NEW), 8);
+    }
+
+    #[test]
+    fn reads_fields_of_remote_struct_by_version() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &[3]);
+        memory.write_bytes(address + 4, &9u32.to_le_bytes());
+
+        let pair = RemoteRef::<Pair>::new(address);
+        assert_eq!(pair.first(&mut memory, OLD).unwrap(), 3);
+        assert_eq!(pair.second(&mut memory, OLD).unwrap(), None);
+        assert_eq!(pair.second(&mut memory, NEW).unwrap(), Some(9));
+    }
+}
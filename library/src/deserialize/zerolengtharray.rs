@@ -1,13 +1,23 @@
-use crate::memory::{Address, MemoryReader};
+use std::fmt;
+
+use crate::memory::{Address, Architecture, MemoryReader};
 
 use super::Error as DeserializeError;
 use super::{ArrayPtr, Deserialize};
 
 pub struct ZeroLengthArray<T: Deserialize> {
+    address: Address,
     array_ptr: ArrayPtr<T>,
 }
 
 impl<T: Deserialize> ZeroLengthArray<T> {
+    /// Gets the address this array was deserialized from. The first element
+    /// begins at this address aligned to the element type's alignment.
+    #[must_use]
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
     pub fn nth_element<M: MemoryReader>(
         &self,
         reader: &mut M,
@@ -26,14 +36,27 @@ impl<T: Deserialize> ZeroLengthArray<T> {
 }
 
 impl<T: Deserialize> Deserialize for ZeroLengthArray<T> {
-    const NUM_BYTES: usize = 0;
-    const ALIGNMENT: usize = T::ALIGNMENT;
+    fn num_bytes<A: Architecture>() -> usize {
+        0
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        T::alignment::<A>()
+    }
 
     fn deserialize<M: MemoryReader>(
         _reader: &mut M,
         address: Address,
     ) -> Result<Self, DeserializeError> {
-        let array_ptr = ArrayPtr::<T>::new(address.align_forward(T::ALIGNMENT));
-        Ok(Self { array_ptr })
+        let array_ptr = ArrayPtr::<T>::new(
+            address.align_forward(T::alignment::<M::Architecture>()),
+        );
+        Ok(Self { address, array_ptr })
+    }
+}
+
+impl<T: Deserialize> fmt::Debug for ZeroLengthArray<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ZeroLengthArray({})", self.address)
     }
 }
@@ -1,12 +1,17 @@
+pub mod buffered;
 pub mod caching;
 pub mod external;
 
 mod address;
+mod architecture;
 mod locator;
 mod reader;
 mod searcher;
+#[cfg(test)]
+pub(crate) mod testing;
 
 pub use address::{Address, AddressRange, VariableLengthAddressRange};
+pub use architecture::{Architecture, Endianness, Native, X86, X86_64};
 pub use locator::MemoryLocator;
 pub use reader::MemoryReader;
 pub use searcher::MemorySearcher;
@@ -2,7 +2,7 @@ use std::fmt;
 use std::ops::Add;
 
 /// A memory address used to identify a location in another process's memory.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Address(usize);
 
 impl Address {
@@ -0,0 +1,67 @@
+use std::mem::{align_of, size_of};
+
+/// The order in which the bytes of an integer are stored in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endianness {
+    /// The most significant byte is stored first.
+    Big,
+
+    /// The least significant byte is stored first.
+    Little,
+}
+
+impl Endianness {
+    /// The byte order of the machine this code is running on.
+    pub const NATIVE: Self = if cfg!(target_endian = "big") {
+        Self::Big
+    } else {
+        Self::Little
+    };
+}
+
+/// Trait for types describing the architecture of a process whose memory is
+/// read, which determines how values are laid out in that memory.
+///
+/// Other architectures can be supported by implementing this trait on a new
+/// type.
+pub trait Architecture {
+    /// The number of bytes in a pointer. This is also the size and alignment of
+    /// `usize`, `isize` and [`Address`](super::Address) values.
+    const POINTER_WIDTH: usize;
+
+    /// The largest alignment required by any primitive integer. Integers are
+    /// aligned to the smaller of their size and this value.
+    const MAX_ALIGNMENT: usize;
+
+    /// The order of the bytes of integers and pointers.
+    const ENDIANNESS: Endianness;
+}
+
+/// The architecture of the machine this code is running on.
+pub struct Native;
+
+impl Architecture for Native {
+    const POINTER_WIDTH: usize = size_of::<usize>();
+    const MAX_ALIGNMENT: usize = align_of::<u64>();
+    const ENDIANNESS: Endianness = Endianness::NATIVE;
+}
+
+/// 32-bit x86 using the System V ABI (as on Linux), where 64-bit integers are
+/// only 4-byte aligned.
+pub struct X86;
+
+impl Architecture for X86 {
+    const POINTER_WIDTH: usize = 4;
+    const MAX_ALIGNMENT: usize = 4;
+    const ENDIANNESS: Endianness = Endianness::Little;
+}
+
+/// 64-bit x86.
+#[allow(non_camel_case_types)]
+pub struct X86_64;
+
+impl Architecture for X86_64 {
+    const POINTER_WIDTH: usize = 8;
+    const MAX_ALIGNMENT: usize = 8;
+    const ENDIANNESS: Endianness = Endianness::Little;
+}
@@ -0,0 +1,180 @@
+use std::io;
+
+use crate::memory::{
+    Address, AddressRange, MemoryReader, VariableLengthAddressRange,
+};
+
+/// A reader that serves reads within a single prefetched range of memory from
+/// a local buffer and forwards all other reads to an underlying reader.
+///
+/// This allows a struct to be read from memory in one operation and then
+/// deserialized field by field without further reads, while pointers inside
+/// the struct can still be followed through the underlying reader.
+pub struct BufferedMemoryReader<'a, M: MemoryReader> {
+    reader: &'a mut M,
+    start: Address,
+    buffer: Vec<u8>,
+}
+
+impl<'a, M: MemoryReader> BufferedMemoryReader<'a, M> {
+    /// Reads all bytes in `range` using `reader` and buffers them for future
+    /// reads.
+    ///
+    /// Returns an IO error if the bytes could not be read.
+    pub fn new(
+        reader: &'a mut M,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Self> {
+        let buffer = reader.read_vec(range)?;
+        Ok(Self::with_buffer(reader, range.start, buffer))
+    }
+
+    /// Creates a reader that serves reads from `buffer`, which holds the bytes
+    /// of memory beginning at `start`, and forwards all other reads to
+    /// `reader`.
+    pub fn with_buffer(
+        reader: &'a mut M,
+        start: Address,
+        buffer: Vec<u8>,
+    ) -> Self {
+        Self {
+            reader,
+            start,
+            buffer,
+        }
+    }
+
+    /// Creates a reader with an empty buffer that forwards every read to
+    /// `reader`.
+    pub fn unbuffered(reader: &'a mut M) -> Self {
+        Self::with_buffer(reader, Address::new(0), Vec::new())
+    }
+
+    // Returns the buffered bytes for the given range, or `None` if any part of
+    // the range lies outside the buffer.
+    fn buffered(&self, start: Address, num_bytes: usize) -> Option<&[u8]> {
+        let offset = start.raw().checked_sub(self.start.raw())?;
+        let end = offset.checked_add(num_bytes)?;
+        self.buffer.get(offset..end)
+    }
+}
+
+impl<'a, M: MemoryReader> MemoryReader for BufferedMemoryReader<'a, M> {
+    type Architecture = M::Architecture;
+
+    fn read_vec(
+        &mut self,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Vec<u8>> {
+        match self.buffered(range.start, range.num_bytes) {
+            Some(bytes) => Ok(bytes.to_vec()),
+            None => self.reader.read_vec(range),
+        }
+    }
+
+    fn read<const NUM_BYTES: usize>(
+        &mut self,
+        range: AddressRange<NUM_BYTES>,
+    ) -> io::Result<[u8; NUM_BYTES]> {
+        match self.buffered(range.start, NUM_BYTES) {
+            Some(bytes) => Ok(bytes.try_into().unwrap()),
+            None => self.reader.read(range),
+        }
+    }
+
+    fn read_vec_uncached(
+        &mut self,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Vec<u8>> {
+        self.reader.read_vec_uncached(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deserialize::{Deserialize, Eager, Ptr};
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Header {
+        kind: u16,
+        length: u32,
+        target: Eager<Ptr<u64>>,
+    }
+
+    #[test]
+    fn serves_reads_within_range_from_buffer() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &0x0123_4567_89ab_cdef_u64.to_le_bytes());
+
+        let mut reader = BufferedMemoryReader::new(
+            &mut memory,
+            VariableLengthAddressRange {
+                start: address,
+                num_bytes: 8,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            u32::deserialize(&mut reader, address).unwrap(),
+            0x89ab_cdef
+        );
+        assert_eq!(u16::deserialize(&mut reader, address + 6).unwrap(), 0x0123);
+        assert_eq!(memory.num_reads(), 1);
+    }
+
+    #[test]
+    fn forwards_reads_outside_range() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &0x0123_4567_89ab_cdef_u64.to_le_bytes());
+
+        let mut reader = BufferedMemoryReader::new(
+            &mut memory,
+            VariableLengthAddressRange {
+                start: address,
+                num_bytes: 4,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            u32::deserialize(&mut reader, address + 2).unwrap(),
+            0x4567_89ab
+        );
+        assert!(u32::deserialize(&mut reader, address + 6).is_err());
+        assert_eq!(memory.num_reads(), 3);
+    }
+
+    #[test]
+    fn reads_derived_struct_in_one_read() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let target = memory.alloc(8);
+        memory.write_bytes(target, &42_u64.to_le_bytes());
+        let address = memory.alloc(Header::num_bytes::<X86_64>());
+        memory.write_bytes(address, &7_u16.to_le_bytes());
+        memory.write_bytes(address + 4, &1234_u32.to_le_bytes());
+        memory.write_ptr(address + 8, target);
+
+        let header = Header::deserialize(&mut memory, address).unwrap();
+        assert_eq!(header.kind, 7);
+        assert_eq!(header.length, 1234);
+        assert_eq!(header.target.value, 42);
+        // One read for the struct and one for the pointer's target.
+        assert_eq!(memory.num_reads(), 2);
+    }
+
+    #[test]
+    fn falls_back_to_field_reads_when_struct_cannot_be_read() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        // Only `kind` and `length` are in memory.
+        let address = memory.alloc(8);
+        memory.write_bytes(address, &7_u16.to_le_bytes());
+
+        let error = Header::deserialize(&mut memory, address).err().unwrap();
+        assert!(error.to_string().starts_with("Header.target (at 0x1008): "));
+    }
+}
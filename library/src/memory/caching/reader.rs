@@ -40,6 +40,8 @@ impl<M: MemoryReader, const PAGE_SIZE: usize>
 impl<M: MemoryReader, const PAGE_SIZE: usize> MemoryReader
     for CachingMemoryReader<M, PAGE_SIZE>
 {
+    type Architecture = M::Architecture;
+
     fn read_vec(
         &mut self,
         range: VariableLengthAddressRange,
@@ -54,8 +56,7 @@ impl<M: MemoryReader, const PAGE_SIZE: usize> MemoryReader
         let range_end = range_start + range.num_bytes;
 
         let first_page_num = range_start / PAGE_SIZE;
-        let last_page_num =
-            first_page_num + (range.num_bytes - 1) / PAGE_SIZE + 1;
+        let last_page_num = (range_end - 1) / PAGE_SIZE + 1;
 
         for page_num in first_page_num..last_page_num {
             let page_data = self.add_page_to_cache(page_num)?;
@@ -71,4 +72,57 @@ impl<M: MemoryReader, const PAGE_SIZE: usize> MemoryReader
 
         Ok(result)
     }
+
+    fn read_vec_uncached(
+        &mut self,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Vec<u8>> {
+        self.reader.read_vec_uncached(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    const PAGE_SIZE: usize = 16;
+
+    fn memory() -> TestMemory<X86_64> {
+        let mut memory = TestMemory::new(Address::new(0x1000));
+        let address = memory.alloc(4 * PAGE_SIZE);
+        let bytes: Vec<u8> = (0..4 * PAGE_SIZE).map(|i| i as u8).collect();
+        memory.write_bytes(address, &bytes);
+        memory
+    }
+
+    fn read(
+        reader: &mut CachingMemoryReader<TestMemory<X86_64>, PAGE_SIZE>,
+        offset: usize,
+        num_bytes: usize,
+    ) -> Vec<u8> {
+        reader
+            .read_vec(VariableLengthAddressRange {
+                start: Address::new(0x1000 + offset),
+                num_bytes,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_unaligned_range_across_page_boundary() {
+        let mut reader = CachingMemoryReader::<_, PAGE_SIZE>::new(memory());
+        assert_eq!(read(&mut reader, 12, 8), (12..20).collect::<Vec<u8>>());
+        assert_eq!(read(&mut reader, 15, 18), (15..33).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn reads_whole_pages() {
+        let mut reader = CachingMemoryReader::<_, PAGE_SIZE>::new(memory());
+        assert_eq!(read(&mut reader, 16, 16), (16..32).collect::<Vec<u8>>());
+        assert_eq!(read(&mut reader, 0, 48), (0..48).collect::<Vec<u8>>());
+        assert_eq!(reader.reader.num_reads(), 3);
+    }
 }
@@ -55,8 +55,6 @@ impl MemoryLocator for ExternalMemoryLocator {
             .filter(|lib| lib.filename.ends_with(library))
             .map(|lib| lib.starting_address)
             .next()
-            .ok_or_else(|| {
-                io::Error::new(io::ErrorKind::Other, "library not found")
-            })
+            .ok_or_else(|| io::Error::other("library not found"))
     }
 }
@@ -1,22 +1,45 @@
 use std::io;
+use std::marker::PhantomData;
 
 use read_process_memory::{copy_address, CopyAddress, ProcessHandle};
 
-use crate::memory::{AddressRange, MemoryReader, VariableLengthAddressRange};
+use crate::memory::{
+    AddressRange, Architecture, MemoryReader, Native,
+    VariableLengthAddressRange,
+};
 
-pub struct ExternalMemoryReader {
+pub struct ExternalMemoryReader<A: Architecture = Native> {
     handle: ProcessHandle,
+    architecture: PhantomData<A>,
 }
 
 impl ExternalMemoryReader {
     pub fn from_pid(pid: i32) -> io::Result<Self> {
         Ok(Self {
             handle: pid.try_into()?,
+            architecture: PhantomData,
         })
     }
 }
 
-impl MemoryReader for ExternalMemoryReader {
+impl<A: Architecture> ExternalMemoryReader<A> {
+    /// Converts this into a reader for the same process that deserializes
+    /// values as laid out on architecture `B`.
+    ///
+    /// Use this when the process was built for a different architecture than
+    /// the one this code is running on (e.g. a 32-bit process).
+    #[must_use]
+    pub fn with_architecture<B: Architecture>(self) -> ExternalMemoryReader<B> {
+        ExternalMemoryReader {
+            handle: self.handle,
+            architecture: PhantomData,
+        }
+    }
+}
+
+impl<A: Architecture> MemoryReader for ExternalMemoryReader<A> {
+    type Architecture = A;
+
     fn read_vec(
         &mut self,
         range: VariableLengthAddressRange,
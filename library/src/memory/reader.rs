@@ -1,9 +1,14 @@
 use std::io;
 
 use super::address::{AddressRange, VariableLengthAddressRange};
+use super::architecture::Architecture;
 
 /// Trait for types that can read byte sequences from memory.
 pub trait MemoryReader {
+    /// The architecture of the process whose memory is read, which determines
+    /// how values are deserialized from it.
+    type Architecture: Architecture;
+
     /// Copies the bytes present in `range` into a vector and returns them.
     ///
     /// The exact context for `range` is type-dependent.
@@ -14,6 +19,23 @@ pub trait MemoryReader {
         range: VariableLengthAddressRange,
     ) -> io::Result<Vec<u8>>;
 
+    /// Copies the bytes present in `range` into a vector and returns them,
+    /// reading them afresh rather than from any cache or buffer.
+    ///
+    /// This is used when memory is expected to have changed since it was last
+    /// read, e.g. to detect torn reads.
+    ///
+    /// Returns an IO error if the bytes could not be read.
+    ///
+    /// The default implementation uses [`read_vec`](MemoryReader::read_vec);
+    /// implementations that cache or buffer memory must override this.
+    fn read_vec_uncached(
+        &mut self,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Vec<u8>> {
+        self.read_vec(range)
+    }
+
     /// Copies the bytes present in `range` into a fixed-size array and returns
     /// them.
     ///
@@ -0,0 +1,93 @@
+use std::io;
+use std::marker::PhantomData;
+
+use crate::memory::{
+    Address, Architecture, MemoryReader, VariableLengthAddressRange, X86_64,
+};
+
+/// An in-memory stand-in for another process's memory, used to build
+/// fixtures for tests.
+///
+/// The memory begins at a fixed address and grows as bytes are written or
+/// allocated. Reads outside the written range fail with an IO error.
+pub(crate) struct TestMemory<A: Architecture = X86_64> {
+    start: Address,
+    bytes: Vec<u8>,
+    num_reads: usize,
+    _architecture: PhantomData<A>,
+}
+
+impl<A: Architecture> TestMemory<A> {
+    /// Creates an empty memory beginning at `start`.
+    pub(crate) fn new(start: Address) -> Self {
+        Self {
+            start,
+            bytes: Vec::new(),
+            num_reads: 0,
+            _architecture: PhantomData,
+        }
+    }
+
+    /// Reserves `num_bytes` zeroed bytes after all existing bytes, aligned to
+    /// the architecture's maximum alignment, and returns their address.
+    pub(crate) fn alloc(&mut self, num_bytes: usize) -> Address {
+        let address =
+            (self.start + self.bytes.len()).align_forward(A::MAX_ALIGNMENT);
+        self.bytes
+            .resize(address.raw() - self.start.raw() + num_bytes, 0);
+        address
+    }
+
+    /// Writes `bytes` at `address`, growing the memory if necessary.
+    pub(crate) fn write_bytes(&mut self, address: Address, bytes: &[u8]) {
+        let offset = address.raw() - self.start.raw();
+        let end = offset + bytes.len();
+        if end > self.bytes.len() {
+            self.bytes.resize(end, 0);
+        }
+        self.bytes[offset..end].copy_from_slice(bytes);
+    }
+
+    /// Writes `target` at `address` as a pointer of the architecture's width.
+    pub(crate) fn write_ptr(&mut self, address: Address, target: Address) {
+        let bytes = target.raw().to_le_bytes();
+        self.write_bytes(address, &bytes[..A::POINTER_WIDTH]);
+    }
+
+    /// Allocates a null-terminated copy of `string` and returns its address.
+    pub(crate) fn alloc_c_string(&mut self, string: &str) -> Address {
+        let address = self.alloc(string.len() + 1);
+        self.write_bytes(address, string.as_bytes());
+        address
+    }
+
+    /// Returns the number of reads made from this memory so far.
+    pub(crate) fn num_reads(&self) -> usize {
+        self.num_reads
+    }
+}
+
+impl<A: Architecture> MemoryReader for TestMemory<A> {
+    type Architecture = A;
+
+    fn read_vec(
+        &mut self,
+        range: VariableLengthAddressRange,
+    ) -> io::Result<Vec<u8>> {
+        self.num_reads += 1;
+        range
+            .start
+            .raw()
+            .checked_sub(self.start.raw())
+            .and_then(|offset| {
+                self.bytes.get(offset..offset.checked_add(range.num_bytes)?)
+            })
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| {
+                io::Error::other(format!(
+                    "Range of {} bytes at {} is out of bounds",
+                    range.num_bytes, range.start
+                ))
+            })
+    }
+}
@@ -1,17 +1,44 @@
 mod class;
+mod domain;
+mod fieldtype;
+mod generic;
 mod ghashtable;
 mod hash;
 mod images;
 mod internalhashtable;
+mod monotype;
 mod object;
-mod utils;
+mod runtime;
+#[cfg(test)]
+mod testing;
+mod unity;
+mod value;
+mod vtable;
+
+use class::FieldCache;
 
 pub use class::{
-    Class, ClassInternals, MonoClassField, MonoClassRuntimeInfo, MonoType,
-    MonoVTable, MONO_TOKEN_TYPE_DEF,
+    Class, ClassInternals, MonoClassField, MonoClassKind, ResolvedField,
+    MONO_TOKEN_TYPE_DEF,
+};
+pub use domain::{
+    AppDomains, MonoAssembly, MonoDomain, MonoDomainGlobals, MONO_ROOT_DOMAIN,
 };
+pub use fieldtype::{MonoFieldType, MonoValueType};
+pub use generic::{MonoGenericClass, MonoGenericInst};
 pub use ghashtable::GHashTable;
 pub use hash::Hash;
-pub use images::{Image, LoadedImages, MonoStreamHeader, MonoTableInfo};
-pub use internalhashtable::MonoInternalHashTable;
+pub use images::{
+    ClassHandle, Image, LoadedImage, LoadedImages, MonoStreamHeader,
+    MonoTableInfo,
+};
+pub use internalhashtable::{
+    Iter as MonoInternalHashTableIter, MonoInternalHashTable,
+    MonoInternalHashValue,
+};
+pub use monotype::{FieldVisibility, MonoType, MonoTypeData, MonoTypeKind};
 pub use object::{Object, ObjectInternals};
+pub use runtime::{MonoRuntime, DEFAULT_MONO_VERSION};
+pub use unity::{Color, Quaternion, Vector2, Vector3};
+pub use value::MonoValue;
+pub use vtable::{MonoClassRuntimeInfo, MonoVTable};
@@ -1,82 +1,139 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, LinkedList};
+use std::fmt;
+use std::rc::Rc;
 
 use crate::deserialize::Error as DeserializeError;
-use crate::deserialize::{ArrayPtr, Deserialize, Eager, Ptr, ZeroLengthArray};
-use crate::memory::{Address, MemoryReader};
+use crate::deserialize::{
+    ArrayPtr, Bitfield, Deserialize, Eager, LazyDeserialize, PathSegment,
+    PrettyPrint, Ptr, RemoteRef, Validate, Version, VersionedDeserialize,
+};
+use crate::memory::{Address, Architecture, MemoryReader};
 
-use super::utils::has_flag;
-use super::{Image, Object, ObjectInternals};
+use super::{
+    Image, MonoClassRuntimeInfo, MonoDomain, MonoGenericClass,
+    MonoInternalHashValue, MonoRuntime, MonoType, MonoVTable, MonoValue,
+    Object, ObjectInternals,
+};
 
 pub const MONO_TOKEN_TYPE_DEF: usize = 0x0200_0000;
 
-const MONO_ROOT_DOMAIN: usize = 0;
+// The tag of the property holding a class's nested classes in its
+// `infrequent_data` (see mono/metadata/class-accessors.c in Mono).
+const PROP_NESTED_CLASSES: i32 = 4;
 
-#[derive(Debug, Deserialize)]
-pub struct MonoType {
-    pub data: Option<Address>,
-    pub attrs: i16,
-    pub typ: u8,
-    _bitfields: u8,
+// See mono/utils/mono-property-bag.h in Mono
+#[derive(Deserialize)]
+pub(super) struct MonoPropertyBagItem {
+    next: Option<Address>,
+    tag: i32,
 }
 
+// The property holding a class's nested classes, which Mono stores as a GList.
 #[derive(Deserialize)]
+pub(super) struct NestedClassesProperty {
+    _head: MonoPropertyBagItem,
+    nested_classes: Option<Ptr<LinkedList<Ptr<Class>>>>,
+}
+
+#[derive(Clone, Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[deserialize(remote)]
 pub struct MonoClassField {
-    pub typ: Ptr<MonoType>,
+    pub typ: Eager<Ptr<MonoType>>,
     pub name: String,
     pub parent: Ptr<Class>,
     pub offset: i32,
 }
 
-#[derive(Deserialize)]
-pub struct MonoVTable {
-    pub class: Ptr<Class>,
-    pub gc_descr: Option<Address>,
-    pub domain: Option<Address>,
-    pub typ: Option<Address>,
-    pub interface_bitmap: Option<Address>,
-    pub max_interface_id: u32,
-    pub rank: u8,
-    pub initialized: u8,
-    bitfield: u32,
-    pub imt_collisions_bitmap: u32,
-    pub runtime_generic_context: Option<Address>,
-    pub vtable: ZeroLengthArray<Address>,
+/// The kind of a [`Class`], which determines how the rest of the class is laid
+/// out. This corresponds to `MonoTypeKind` in mono/metadata/class-internals.h.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonoClassKind {
+    /// A non-generic class defined in metadata.
+    Definition,
+    /// A generic type definition, e.g. `List<T>`.
+    GenericDefinition,
+    /// An instantiation of a generic type definition, e.g. `List<string>`.
+    GenericInstance,
+    GenericParameter,
+    Array,
+    Pointer,
 }
 
-impl MonoVTable {
-    #[must_use]
-    pub fn remote(&self) -> bool {
-        has_flag(self.bitfield, 0b1000_0000_0000_0000_0000_0000_0000_0000)
-    }
-
-    #[must_use]
-    pub fn init_failed(&self) -> bool {
-        has_flag(self.bitfield, 0b0100_0000_0000_0000_0000_0000_0000_0000)
-    }
+impl TryFrom<u8> for MonoClassKind {
+    type Error = DeserializeError;
 
-    #[must_use]
-    pub fn has_static_fields(&self) -> bool {
-        has_flag(self.bitfield, 0b0010_0000_0000_0000_0000_0000_0000_0000)
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            1 => Self::Definition,
+            2 => Self::GenericDefinition,
+            3 => Self::GenericInstance,
+            4 => Self::GenericParameter,
+            5 => Self::Array,
+            6 => Self::Pointer,
+            _ => {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Unknown class kind {value}"
+                )))
+            }
+        })
     }
 }
 
-#[derive(Deserialize)]
-pub struct MonoClassRuntimeInfo {
-    pub max_domain: u16,
-    pub domain_vtables: ZeroLengthArray<Eager<Ptr<MonoVTable>>>,
-}
-
-#[derive(Deserialize)]
+// The fields from `flags` onwards are only present in classes of kind
+// `Definition` or `GenericDefinition` (MonoClassDef in Mono).
+#[derive(Bitfield, PrettyPrint, VersionedDeserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[versioned(remote)]
 pub struct ClassInternals {
     pub element_class: Option<Address>,
     pub cast_class: Option<Address>,
     pub supertypes: Option<Address>,
     pub idepth: u16,
     pub rank: u8,
+    pub class_kind: u8,
     pub instance_size: i32,
-    _bitfields_group_1: u8,
+    #[bitfield(
+        pub inited: bool = 0,
+        pub size_inited: bool = 1,
+        pub valuetype: bool = 2,
+        pub enumtype: bool = 3,
+        pub blittable: bool = 4,
+        pub unicode: bool = 5,
+        pub wastypebuilder: bool = 6,
+        pub is_array_special_interface: bool = 7,
+    )]
+    bitfields_1: u8,
     pub min_align: u8,
-    _bitfields_group_2: [u8; 4],
+    #[bitfield(
+        pub packing_size: u8 = 0..4,
+        pub ghcimpl: bool = 4,
+        pub has_finalize: bool = 5,
+        pub marshalbyref: bool = 6,
+        pub contextbound: bool = 7,
+    )]
+    bitfields_2: u8,
+    #[bitfield(
+        pub delegate: bool = 0,
+        pub gc_descr_inited: bool = 1,
+        pub has_cctor: bool = 2,
+        pub has_references: bool = 3,
+        pub has_static_refs: bool = 4,
+        pub no_special_static_fields: bool = 5,
+        pub is_com_object: bool = 6,
+        pub nested_classes_inited: bool = 7,
+    )]
+    bitfields_3: u8,
+    #[bitfield(
+        pub interfaces_inited: bool = 0,
+        pub simd_type: bool = 1,
+        pub has_finalize_inited: bool = 2,
+        pub fields_inited: bool = 3,
+        pub has_failure: bool = 4,
+        pub has_weak_fields: bool = 5,
+    )]
+    bitfields_4: u8,
     pub parent: Option<Ptr<Class>>,
     pub nested_in: Option<Ptr<Class>>,
     pub image: Option<Ptr<Image>>,
@@ -93,7 +150,7 @@ pub struct ClassInternals {
     pub interface_bitmap: Option<Address>,
     pub interfaces: Option<Address>,
     pub sizes: i32,
-    pub fields: ArrayPtr<MonoClassField>,
+    pub fields: Option<ArrayPtr<MonoClassField>>,
     pub methods: Option<Address>,
     pub this_arg: MonoType,
     pub byval_arg: MonoType,
@@ -111,116 +168,1009 @@ pub struct ClassInternals {
 }
 
 impl ClassInternals {
+    /// Reads the class at `address`, laid out according to the runtime
+    /// version `version`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the class cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        version: Version,
+    ) -> Result<Self, DeserializeError> {
+        Self::deserialize_versioned(reader, address, version)
+    }
+
+    // Reads the vtable of the class in the domain with ID `domain_id`, or
+    // None if the class has not been initialized in that domain.
     fn deserialize_vtable<M: MemoryReader>(
         &self,
         reader: &mut M,
+        domain_id: usize,
+        version: Version,
     ) -> Result<Option<MonoVTable>, DeserializeError> {
-        let Some(ref runtime_info) = self.runtime_info.value else {
-            return Ok(None);
-        };
+        match self.runtime_info.value {
+            Some(ref runtime_info) => {
+                runtime_info.vtable(reader, domain_id, version)
+            }
+            None => Ok(None),
+        }
+    }
 
-        // Clippy suggests == MONO_ROOT_DOMAIN, but if MONO_ROOT_DOMAIN changes,
-        // this won't work.
-        #[allow(clippy::absurd_extreme_comparisons)]
-        if usize::from(runtime_info.max_domain) <= MONO_ROOT_DOMAIN {
-            Err(DeserializeError::InvalidStateError(format!(
-                "max_domain [{}] is less than MONO_ROOT_DOMAIN [{MONO_ROOT_DOMAIN}]",
-                runtime_info.max_domain,
-            )))
-        } else {
-            Ok(Some(
-                runtime_info
-                    .domain_vtables
-                    .nth_element(reader, MONO_ROOT_DOMAIN)?
-                    .value,
-            ))
+    /// Reads the classes nested directly in this class.
+    ///
+    /// Returns an [`Error`](DeserializeError) if Mono has not set up the
+    /// nested classes of this class yet, or they cannot be read.
+    pub fn nested_classes<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Vec<Ptr<Class>>, DeserializeError> {
+        if !self.nested_classes_inited() {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Nested classes of class \"{}\" have not been set up",
+                self.name
+            )));
+        }
+
+        // Mono keeps the properties sorted by tag, so the search can stop at
+        // the first property with a larger tag.
+        let mut previous_tag = 0;
+        let mut next_item = self.infrequent_data;
+        while let Some(item_addr) = next_item {
+            let item = MonoPropertyBagItem::deserialize(reader, item_addr)?;
+            if item.tag <= previous_tag {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Properties of class \"{}\" are not sorted by tag at {}",
+                    self.name, item_addr
+                )));
+            }
+            if item.tag == PROP_NESTED_CLASSES {
+                let property =
+                    NestedClassesProperty::deserialize(reader, item_addr)?;
+                return match property.nested_classes {
+                    Some(list) => Ok(list.deref(reader)?.into_iter().collect()),
+                    None => Ok(Vec::new()),
+                };
+            }
+            if item.tag > PROP_NESTED_CLASSES {
+                break;
+            }
+            previous_tag = item.tag;
+            next_item = item.next;
+        }
+        Ok(Vec::new())
+    }
+
+    // Reads the fields of the class at `address`, including those inherited
+    // from its ancestors. Fields declared closer to the class hide fields with
+    // the same name declared further up the hierarchy. The fields of the class
+    // and its ancestors are memoized in `cache`, and only ancestors that are
+    // not yet memoized are read.
+    fn deserialize_fields<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        address: Address,
+        version: Version,
+        cache: &FieldCache,
+    ) -> Result<FieldMap, DeserializeError> {
+        let mut unresolved = Vec::new();
+        let mut visited = HashSet::new();
+        let mut next_class = Some(address);
+        let mut fields = FieldMap::new();
+        while let Some(class_addr) = next_class {
+            if let Some(cached_fields) = cache.get(class_addr) {
+                fields = cached_fields;
+                break;
+            }
+            if !visited.insert(class_addr) {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Class \"{}\" has a cycle in its parent classes at {}",
+                    self.name, class_addr
+                )));
+            }
+            unresolved.push(class_addr);
+            next_class = RemoteRef::<ClassInternals>::new(class_addr)
+                .parent(reader, version)?
+                .as_ref()
+                .map(Ptr::address);
+        }
+
+        // Resolve the fields of each class from those of its parent, starting
+        // from the ancestor furthest up the hierarchy.
+        for class_addr in unresolved.into_iter().rev() {
+            for resolved in fields.values_mut() {
+                resolved.depth += 1;
+            }
+            let class_name = RemoteRef::<ClassInternals>::new(class_addr)
+                .name(reader, version)?;
+            for field in read_declared_fields(reader, class_addr, version)? {
+                fields.insert(
+                    field.name.clone(),
+                    ResolvedField {
+                        field,
+                        declaring_class: class_name.clone(),
+                        declaring_class_address: class_addr,
+                        depth: 0,
+                    },
+                );
+            }
+            cache.insert(class_addr, fields.clone());
         }
+        Ok(fields)
     }
+
+    /// Decodes the kind of this class.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the kind is not known.
+    pub fn kind(&self) -> Result<MonoClassKind, DeserializeError> {
+        MonoClassKind::try_from(self.class_kind)
+    }
+}
+
+// Reads the generic instance that the generic instance class at `address` is
+// created from. MonoClassGenericInst stores a pointer to it directly after the
+// fields shared by all classes, where MonoClassDef stores `flags`.
+fn read_generic_class<M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+    version: Version,
+) -> Result<MonoGenericClass, DeserializeError> {
+    let offset =
+        ClassInternals::offset_of::<M::Architecture>(version, "flags").unwrap();
+    Ptr::<MonoGenericClass>::deserialize(reader, address + offset)?
+        .deref(reader)
 }
 
+// Reads the fields declared directly by the class at `address`. Generic
+// instances store their own fields with the type arguments substituted, but
+// only their generic type definition stores how many fields there are.
+fn read_declared_fields<M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+    version: Version,
+) -> Result<Vec<MonoClassField>, DeserializeError> {
+    let class = RemoteRef::<ClassInternals>::new(address);
+    let (field_count, fields) =
+        match MonoClassKind::try_from(class.class_kind(reader, version)?)? {
+            MonoClassKind::Definition | MonoClassKind::GenericDefinition => (
+                class.field_count(reader, version)?,
+                class.fields(reader, version)?,
+            ),
+            MonoClassKind::GenericInstance => {
+                let definition = RemoteRef::<ClassInternals>::new(
+                    read_generic_class(reader, address, version)?
+                        .container_class
+                        .address(),
+                );
+                (
+                    definition.field_count(reader, version)?,
+                    class.fields(reader, version)?,
+                )
+            }
+            MonoClassKind::GenericParameter
+            | MonoClassKind::Array
+            | MonoClassKind::Pointer => (0, None),
+        };
+
+    if field_count == 0 {
+        return Ok(Vec::new());
+    }
+    let Some(fields) = fields else {
+        return Err(DeserializeError::InvalidStateError(format!(
+            "Fields of class \"{}\" have not been set up",
+            class.name(reader, version)?
+        )));
+    };
+    fields.deref(reader, field_count.try_into()?)
+}
+
+// Reads the address of the static field data of a class from its vtable in
+// some domain. The data pointer is stored after the vtable's method slots.
+fn read_static_field_data<M: MemoryReader>(
+    reader: &mut M,
+    internals: &ClassInternals,
+    vtable: Option<&MonoVTable>,
+) -> Result<Option<Address>, DeserializeError> {
+    match vtable {
+        Some(vtable) => Ok(Some(
+            vtable
+                .vtable
+                .nth_element(reader, internals.vtable_size.try_into()?)?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// A field of a class, which may be declared by the class itself or inherited
+/// from one of its ancestors.
+#[derive(Clone, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ResolvedField {
+    pub field: MonoClassField,
+    /// The name of the class that declares the field.
+    pub declaring_class: String,
+    /// The address of the class that declares the field.
+    pub declaring_class_address: Address,
+    /// The number of steps up the class hierarchy to the declaring class, or
+    /// 0 if the field is declared by the class itself.
+    pub depth: usize,
+}
+
+type FieldMap = HashMap<String, ResolvedField>;
+
+/// A memo of the fields of classes, including inherited fields, by class
+/// address. Clones share the same memo.
+#[derive(Clone, Default)]
+pub(super) struct FieldCache {
+    fields_by_class: Rc<RefCell<HashMap<Address, FieldMap>>>,
+}
+
+impl FieldCache {
+    fn get(&self, class: Address) -> Option<FieldMap> {
+        self.fields_by_class.borrow().get(&class).cloned()
+    }
+
+    fn insert(&self, class: Address, fields: FieldMap) {
+        self.fields_by_class.borrow_mut().insert(class, fields);
+    }
+}
+
+impl PrettyPrint for FieldCache {
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        _depth: usize,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "FieldCache {{.. {} classes}}",
+            self.fields_by_class.borrow().len()
+        )
+    }
+}
+
+#[derive(PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct Class {
+    pub address: Address,
     pub internals: ClassInternals,
-    _vtable: Option<MonoVTable>,
-    fields: HashMap<String, MonoClassField>,
+    vtable: Option<MonoVTable>,
+    generic_class: Option<MonoGenericClass>,
+    fields: FieldMap,
     static_field_data: Option<Address>,
+    runtime: MonoRuntime,
+}
+
+impl Validate for Class {
+    fn validate(&self, address: Address) -> Result<(), DeserializeError> {
+        if let Some(ref vtable) = self.vtable {
+            if vtable.class.address() != address {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "VTable of class \"{}\" points to class at {} instead of \
+                     {address}",
+                    self.internals.name,
+                    vtable.class.address(),
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
-impl Deserialize for Class {
-    const NUM_BYTES: usize = ClassInternals::NUM_BYTES;
-    const ALIGNMENT: usize = ClassInternals::ALIGNMENT;
+// Mono's class cache is keyed by type token and chained through
+// next_class_cache (see class_key_extract and class_next_value in
+// mono/metadata/class.c).
+impl MonoInternalHashValue for Class {
+    type Key = u32;
+
+    fn key<M: MemoryReader>(
+        reader: &mut M,
+        value: &Ptr<Self>,
+        version: Version,
+    ) -> Result<u32, DeserializeError> {
+        RemoteRef::<ClassInternals>::new(value.address())
+            .type_token(reader, version)
+    }
+
+    fn next_value<M: MemoryReader>(
+        reader: &mut M,
+        value: &Ptr<Self>,
+        version: Version,
+    ) -> Result<Option<Ptr<Self>>, DeserializeError> {
+        RemoteRef::<ClassInternals>::new(value.address())
+            .next_class_cache(reader, version)
+    }
+}
 
-    fn deserialize<M: MemoryReader>(
+impl Class {
+    /// Reads the class at `address` from a target whose runtime is described
+    /// by `runtime`, using its vtable in the domain the runtime reads static
+    /// fields from.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the class or its vtable
+    /// cannot be read, or the vtable does not belong to the class.
+    pub fn read<M: MemoryReader>(
         reader: &mut M,
         address: Address,
+        runtime: &MonoRuntime,
     ) -> Result<Self, DeserializeError> {
-        let internals = ClassInternals::deserialize(reader, address)?;
-        let vtable = internals.deserialize_vtable(reader).map_err(|error| {
-            DeserializeError::WithContext(Box::new(error), "vtable".to_string())
-        })?;
+        let class_field = |field_name| PathSegment::Field {
+            struct_name: "Class",
+            field_name,
+            address,
+        };
 
-        let mut fields = HashMap::<String, MonoClassField>::new();
-        // TODO: This doesn't work with container classes, generic classes, etc.
-        for i in 0..internals.field_count.try_into()? {
-            let field =
-                internals.fields.nth_element(reader, i).map_err(|error| {
-                    DeserializeError::WithContext(
-                        Box::new(error),
-                        format!("fields.{i}"),
-                    )
-                })?;
-            fields.insert(field.name.clone(), field);
-        }
+        let internals = ClassInternals::read(reader, address, runtime.version)
+            .map_err(|error| error.within(class_field("internals")))?;
+        let vtable = internals
+            .deserialize_vtable(reader, runtime.domain_id, runtime.version)
+            .map_err(|error| error.within(class_field("vtable")))?;
 
-        let static_field_data = match vtable {
-            Some(ref the_vtable) /*if the_vtable.has_static_fields()*/ => {
-                Some(
-                    the_vtable.vtable.nth_element(
-                        reader,
-                        internals.vtable_size.try_into()?,
-                    )?
-                )
-            },
-            _ => None
+        let generic_class = match internals.kind()? {
+            MonoClassKind::GenericInstance => Some(
+                read_generic_class(reader, address, runtime.version).map_err(
+                    |error| error.within(class_field("generic_class")),
+                )?,
+            ),
+            _ => None,
         };
 
-        Ok(Self {
+        let fields = internals
+            .deserialize_fields(
+                reader,
+                address,
+                runtime.version,
+                runtime.field_cache(),
+            )
+            .map_err(|error| error.within(class_field("fields")))?;
+
+        let static_field_data =
+            read_static_field_data(reader, &internals, vtable.as_ref())?;
+
+        let class = Self {
+            address,
             internals,
-            _vtable: vtable,
+            vtable,
+            generic_class,
             fields,
             static_field_data,
+            runtime: runtime.clone(),
+        };
+        class.validate(address).map_err(|error| {
+            error.within(PathSegment::Struct {
+                struct_name: "Class",
+                address,
+            })
+        })?;
+        Ok(class)
+    }
+
+    /// Gets the field named `name` of this class, which may be inherited from
+    /// one of its ancestors, along with the class that declares it.
+    ///
+    /// Returns an [`Error`](DeserializeError) if there is no such field.
+    pub fn resolve_field(
+        &self,
+        name: &str,
+    ) -> Result<&ResolvedField, DeserializeError> {
+        self.fields.get(name).ok_or_else(|| {
+            DeserializeError::InvalidStateError(format!(
+                "Field \"{}\" does not exist on class \"{}\"",
+                name, self.internals.name
+            ))
         })
     }
-}
 
-impl Class {
-    pub fn get_static_field_address(
+    /// Gets the field named `name` of this class, which may be inherited from
+    /// one of its ancestors.
+    ///
+    /// Returns an [`Error`](DeserializeError) if there is no such field.
+    pub fn get_field(
+        &self,
+        name: &str,
+    ) -> Result<&MonoClassField, DeserializeError> {
+        self.resolve_field(name).map(|resolved| &resolved.field)
+    }
+
+    /// Gets the address of the static field `name` of this class in the
+    /// domain it reads from. Inherited static fields are stored with the class
+    /// that declares them, so they are read through that class's vtable.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the field does not exist, is
+    /// not static, is a constant (which has no storage; see
+    /// [`LoadedImage::read_string_constant`](super::LoadedImage::read_string_constant)),
+    /// or its declaring class has not been initialized in the domain.
+    pub fn get_static_field_address<M: MemoryReader>(
         &self,
+        reader: &mut M,
         name: &str,
     ) -> Result<Address, DeserializeError> {
-        let static_field_data = self.static_field_data.ok_or_else(|| {
-            DeserializeError::InvalidStateError(
-                "No static field data".to_string(),
-            )
-        })?;
-        let Some(field) = self.fields.get(name) else {
+        let resolved = self.resolve_field(name)?;
+        if !resolved.field.typ.value.is_static() {
             return Err(DeserializeError::InvalidStateError(format!(
-                "Field \"{}\" does not exist on class \"{}\"",
-                name, &self.internals.name
+                "Field \"{}\" of class \"{}\" is not static",
+                name, self.internals.name
+            )));
+        }
+        if resolved.field.typ.value.is_literal() {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Field \"{}\" of class \"{}\" is a constant and has no \
+                 storage",
+                name, self.internals.name
             )));
+        }
+        let static_field_data = if resolved.depth == 0 {
+            self.static_field_data
+        } else {
+            let declaring_class = ClassInternals::read(
+                reader,
+                resolved.declaring_class_address,
+                self.runtime.version,
+            )?;
+            let vtable = declaring_class.deserialize_vtable(
+                reader,
+                self.runtime.domain_id,
+                self.runtime.version,
+            )?;
+            read_static_field_data(reader, &declaring_class, vtable.as_ref())?
         };
-        // TODO: perform safety checks (e.g. that field is static)
-        Ok(static_field_data + field.offset.try_into()?)
+        let static_field_data = static_field_data.ok_or_else(|| {
+            DeserializeError::InvalidStateError(format!(
+                "Class \"{}\" has no static field data in domain [{}]",
+                resolved.declaring_class, self.runtime.domain_id
+            ))
+        })?;
+        Ok(static_field_data + resolved.field.offset.try_into()?)
+    }
+
+    pub fn get_static_field_object<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+    ) -> Result<Object, DeserializeError> {
+        let address = self.get_static_field_address(reader, name)?;
+        let object = Address::deserialize(reader, address)?;
+        Object::read(reader, object, &self.runtime)
     }
 
-    pub fn get_static_field_object<'a, M: MemoryReader>(
-        &'a self,
+    /// Reads the static field `name` of this class as a [`MonoValue`] based on
+    /// the field's type.
+    ///
+    /// Returns an [`Error`](DeserializeError) under the same conditions as
+    /// [`get_static_field_address`](Class::get_static_field_address), or if
+    /// the value cannot be read.
+    pub fn read_static_value<M: MemoryReader>(
+        &self,
         reader: &mut M,
         name: &str,
-    ) -> Result<Object<'a>, DeserializeError> {
-        let address = self.get_static_field_address(name)?;
-        Ok(Object {
-            internals: ObjectInternals::deserialize(reader, address)?,
-            class: self,
-        })
+    ) -> Result<MonoValue, DeserializeError> {
+        let address = self.get_static_field_address(reader, name)?;
+        MonoValue::read(
+            reader,
+            &self.get_field(name)?.typ.value,
+            address,
+            &self.runtime,
+        )
+    }
+
+    /// Gets the generic instance this class is created from, if it is an
+    /// instantiation of a generic type definition.
+    #[must_use]
+    pub fn generic_class(&self) -> Option<&MonoGenericClass> {
+        self.generic_class.as_ref()
+    }
+
+    /// Reads the type arguments of this class, in order, if it is an
+    /// instantiation of a generic type definition. Returns an empty vector
+    /// otherwise.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the type arguments cannot be
+    /// read.
+    pub fn type_arguments<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Vec<MonoType>, DeserializeError> {
+        match self.generic_class {
+            Some(ref generic_class) => generic_class.type_arguments(reader),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Gets the description of the runtime this class was read from.
+    #[must_use]
+    pub fn runtime(&self) -> &MonoRuntime {
+        &self.runtime
+    }
+
+    /// Gets the ID of the domain whose static fields this class reads.
+    #[must_use]
+    pub fn domain_id(&self) -> usize {
+        self.runtime.domain_id
+    }
+
+    /// Switches this class to reading static fields from `domain` instead of
+    /// the domain it currently reads from.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the class has not been
+    /// initialized in `domain` or its vtable in `domain` cannot be read.
+    pub fn in_domain<M: MemoryReader>(
+        mut self,
+        reader: &mut M,
+        domain: &MonoDomain,
+    ) -> Result<Self, DeserializeError> {
+        let domain_id = usize::try_from(domain.domain_id)?;
+        let vtable = self
+            .internals
+            .deserialize_vtable(reader, domain_id, self.runtime.version)?
+            .ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Class \"{}\" has not been initialized in domain [{}]",
+                    self.internals.name, domain_id
+                ))
+            })?;
+        self.static_field_data =
+            read_static_field_data(reader, &self.internals, Some(&vtable))?;
+        self.vtable = Some(vtable);
+        self.runtime.domain_id = domain_id;
+        self.validate(self.address)?;
+        Ok(self)
+    }
+
+    /// Iterates over the fields of this class, including inherited fields, in
+    /// no particular order.
+    pub fn fields(&self) -> impl Iterator<Item = &ResolvedField> {
+        self.fields.values()
+    }
+
+    /// Returns whether this class is a value type (including enums and
+    /// primitive types).
+    #[must_use]
+    pub fn is_value_type(&self) -> bool {
+        self.internals.valuetype()
+    }
+
+    /// Returns whether this class is an enum.
+    #[must_use]
+    pub fn is_enum(&self) -> bool {
+        self.internals.enumtype()
+    }
+
+    /// Gets the number of bytes that a value of this class occupies when it
+    /// is stored in a field or array element on architecture `A`: the size of
+    /// the value for value types, or the size of a reference otherwise.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the instance size is invalid.
+    pub fn element_size<A: Architecture>(
+        &self,
+    ) -> Result<usize, DeserializeError> {
+        if !self.is_value_type() {
+            return Ok(A::POINTER_WIDTH);
+        }
+        // The instance size of a value type includes the header it has when
+        // boxed.
+        usize::try_from(self.internals.instance_size)?
+            .checked_sub(<ObjectInternals as Deserialize>::num_bytes::<A>())
+            .ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Value type \"{}\" has instance size [{}], which is \
+                     smaller than an object header",
+                    self.internals.name, self.internals.instance_size
+                ))
+            })
+    }
+
+    /// Gets the offset of the instance field `field` of this value type from
+    /// the start of a value stored inline (i.e. without an object header) on
+    /// architecture `A`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the field's offset does not
+    /// include an object header.
+    pub fn inline_field_offset<A: Architecture>(
+        &self,
+        field: &MonoClassField,
+    ) -> Result<usize, DeserializeError> {
+        usize::try_from(field.offset)?
+            .checked_sub(<ObjectInternals as Deserialize>::num_bytes::<A>())
+            .ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Field \"{}\" of value type \"{}\" has offset [{}], \
+                     which is inside the object header",
+                    field.name, self.internals.name, field.offset
+                ))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{X86, X86_64};
+    use crate::mono::testing::{
+        assert_versioned_layout, class_offset, offset_of, write_class,
+        write_fields, write_runtime_info, write_vtable,
+    };
+    use crate::mono::{
+        MonoGenericClass, MonoGenericInst, DEFAULT_MONO_VERSION,
+        MONO_ROOT_DOMAIN,
+    };
+
+    use super::*;
+
+    const I4: u8 = 0x08;
+    const STATIC: i16 = 0x0010;
+    const LITERAL: i16 = 0x0040;
+
+    #[test]
+    fn class_decodes_flags_in_declaration_order() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_class(&mut memory, "System", "Test");
+        let offset = class_offset;
+        memory.write_bytes(address + offset("bitfields_1"), &[0b0100_1101]);
+        memory.write_bytes(address + offset("min_align"), &[8]);
+        memory.write_bytes(address + offset("bitfields_2"), &[0b1010_0011]);
+        memory.write_bytes(address + offset("bitfields_3"), &[0b0101_1001]);
+        memory.write_bytes(address + offset("bitfields_4"), &[0b0010_1010]);
+
+        let class =
+            ClassInternals::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        assert!(class.inited());
+        assert!(!class.size_inited());
+        assert!(class.valuetype());
+        assert!(class.enumtype());
+        assert!(!class.blittable());
+        assert!(!class.unicode());
+        assert!(class.wastypebuilder());
+        assert!(!class.is_array_special_interface());
+        assert_eq!(class.min_align, 8);
+        assert_eq!(class.packing_size(), 3);
+        assert!(!class.ghcimpl());
+        assert!(class.has_finalize());
+        assert!(!class.marshalbyref());
+        assert!(class.contextbound());
+        assert!(class.delegate());
+        assert!(!class.gc_descr_inited());
+        assert!(!class.has_cctor());
+        assert!(class.has_references());
+        assert!(class.has_static_refs());
+        assert!(!class.no_special_static_fields());
+        assert!(class.is_com_object());
+        assert!(!class.nested_classes_inited());
+        assert!(!class.interfaces_inited());
+        assert!(class.simd_type());
+        assert!(!class.has_finalize_inited());
+        assert!(class.fields_inited());
+        assert!(!class.has_failure());
+        assert!(class.has_weak_fields());
+        assert_eq!(class.name, "Test");
+        assert_eq!(class.name_space, "System");
+    }
+
+    #[test]
+    fn reads_vtable_laid_out_for_runtime_version() {
+        let mono_6_12 = Version::new(6, 12, 0, 0);
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_class(&mut memory, "", "Test");
+        let static_data = Address::new(0x3000);
+        let vtable =
+            write_vtable(&mut memory, address, mono_6_12, &[static_data]);
+        write_runtime_info(&mut memory, address, &[vtable]);
+
+        let class =
+            Class::read(&mut memory, address, &MonoRuntime::new(mono_6_12))
+                .unwrap();
+        assert_eq!(class.runtime().version, mono_6_12);
+        assert_eq!(class.static_field_data, Some(static_data));
+
+        // With the base layout, the null interpreter vtable pointer is read
+        // as the static data pointer.
+        assert!(Class::read(
+            &mut memory,
+            address,
+            &MonoRuntime::new(DEFAULT_MONO_VERSION),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn value_types_and_enums_are_read_from_flags() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        // Although it derives from System.Enum, System.Enum itself is not a
+        // value type.
+        let value_type = write_class(&mut memory, "System", "ValueType");
+        let enum_class = write_class(&mut memory, "System", "Enum");
+        memory.write_ptr(enum_class + class_offset("parent"), value_type);
+        let color = write_class(&mut memory, "", "Color");
+        memory.write_ptr(color + class_offset("parent"), enum_class);
+        // valuetype is bit 2 and enumtype bit 3 of bitfields_1.
+        memory.write_bytes(color + class_offset("bitfields_1"), &[0b1100]);
+        memory.write_bytes(
+            color + class_offset("instance_size"),
+            &20_i32.to_le_bytes(),
+        );
+
+        let enum_class =
+            Class::read(&mut memory, enum_class, &MonoRuntime::default())
+                .unwrap();
+        assert!(!enum_class.is_value_type());
+        assert!(!enum_class.is_enum());
+        assert_eq!(enum_class.element_size::<X86>().unwrap(), 4);
+        assert_eq!(enum_class.element_size::<X86_64>().unwrap(), 8);
+
+        let color =
+            Class::read(&mut memory, color, &MonoRuntime::default()).unwrap();
+        assert!(color.is_value_type());
+        assert!(color.is_enum());
+        assert_eq!(color.element_size::<X86_64>().unwrap(), 4);
+    }
+
+    #[test]
+    fn inline_field_offset_excludes_object_header() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_class(&mut memory, "", "Point");
+        write_fields(&mut memory, address, &[("y", I4, 0, 20)]);
+        let class =
+            Class::read(&mut memory, address, &MonoRuntime::default()).unwrap();
+        let field = class.get_field("y").unwrap();
+        assert_eq!(class.inline_field_offset::<X86_64>(field).unwrap(), 4);
+        assert_eq!(class.inline_field_offset::<X86>(field).unwrap(), 12);
+    }
+
+    #[test]
+    fn memoizes_fields_of_ancestors() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let base = write_class(&mut memory, "", "Base");
+        write_fields(&mut memory, base, &[("id", I4, 0, 16)]);
+        let first = write_class(&mut memory, "", "First");
+        memory.write_ptr(first + class_offset("parent"), base);
+        let second = write_class(&mut memory, "", "Second");
+        memory.write_ptr(second + class_offset("parent"), base);
+
+        let runtime = MonoRuntime::default();
+        let first = Class::read(&mut memory, first, &runtime).unwrap();
+        assert_eq!(first.resolve_field("id").unwrap().depth, 1);
+
+        // Break the fields of the base class, which are only read again
+        // without the memo.
+        memory.write_ptr(base + class_offset("fields"), Address::new(0x10));
+        let second_class = Class::read(&mut memory, second, &runtime).unwrap();
+        let id = second_class.resolve_field("id").unwrap();
+        assert_eq!(id.declaring_class, "Base");
+        assert_eq!(id.declaring_class_address, base);
+        assert_eq!(id.depth, 1);
+        assert!(
+            Class::read(&mut memory, second, &MonoRuntime::default()).is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_generic_instance_whose_fields_are_not_set_up() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let definition = write_class(&mut memory, "Game", "Box`1");
+        memory.write_bytes(definition + class_offset("class_kind"), &[2]);
+        write_fields(&mut memory, definition, &[("value", I4, 0, 16)]);
+        let generic_class = memory
+            .alloc(<MonoGenericClass as Deserialize>::num_bytes::<X86_64>());
+        memory.write_ptr(
+            generic_class + offset_of::<MonoGenericClass>("container_class"),
+            definition,
+        );
+        let class_inst = memory
+            .alloc(<MonoGenericInst as Deserialize>::num_bytes::<X86_64>());
+        memory.write_ptr(
+            generic_class + offset_of::<MonoGenericClass>("class_inst"),
+            class_inst,
+        );
+        let instance = write_class(&mut memory, "Game", "Box`1");
+        memory.write_bytes(instance + class_offset("class_kind"), &[3]);
+        memory.write_ptr(instance + class_offset("flags"), generic_class);
+
+        let runtime = MonoRuntime::default();
+        let error = Class::read(&mut memory, instance, &runtime).err().unwrap();
+        assert!(matches!(
+            error.root_cause(),
+            DeserializeError::InvalidStateError(message)
+                if message.contains("have not been set up")
+        ));
+
+        write_fields(&mut memory, instance, &[("value", I4, 0, 24)]);
+        let class = Class::read(&mut memory, instance, &runtime).unwrap();
+        assert_eq!(class.get_field("value").unwrap().offset, 24);
+    }
+
+    #[test]
+    fn reads_inherited_static_field_from_declaring_class() {
+        let mono_6_12 = Version::new(6, 12, 0, 0);
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let base = write_class(&mut memory, "", "Base");
+        write_fields(&mut memory, base, &[("count", I4, STATIC, 4)]);
+        let base_data = Address::new(0x3000);
+        let vtable = write_vtable(&mut memory, base, mono_6_12, &[base_data]);
+        write_runtime_info(&mut memory, base, &[vtable]);
+        let derived = write_class(&mut memory, "", "Derived");
+        memory.write_ptr(derived + class_offset("parent"), base);
+        write_fields(&mut memory, derived, &[("own", I4, STATIC, 8)]);
+        let derived_data = Address::new(0x4000);
+        let vtable =
+            write_vtable(&mut memory, derived, mono_6_12, &[derived_data]);
+        write_runtime_info(&mut memory, derived, &[vtable]);
+
+        let class =
+            Class::read(&mut memory, derived, &MonoRuntime::new(mono_6_12))
+                .unwrap();
+        assert_eq!(
+            class.get_static_field_address(&mut memory, "own").unwrap(),
+            derived_data + 8
+        );
+        assert_eq!(
+            class
+                .get_static_field_address(&mut memory, "count")
+                .unwrap(),
+            base_data + 4
+        );
+    }
+
+    #[test]
+    fn rejects_static_address_of_constant() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let class = write_class(&mut memory, "", "Consts");
+        write_fields(
+            &mut memory,
+            class,
+            &[("Max", I4, STATIC | LITERAL, 0), ("count", I4, STATIC, 0)],
+        );
+        let data = Address::new(0x3000);
+        let vtable =
+            write_vtable(&mut memory, class, DEFAULT_MONO_VERSION, &[data]);
+        write_runtime_info(&mut memory, class, &[vtable]);
+
+        let class =
+            Class::read(&mut memory, class, &MonoRuntime::default()).unwrap();
+        let error = class
+            .get_static_field_address(&mut memory, "Max")
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            DeserializeError::InvalidStateError(ref message)
+                if message.contains("is a constant")
+        ));
+        assert_eq!(
+            class
+                .get_static_field_address(&mut memory, "count")
+                .unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn reads_static_fields_in_domain_of_runtime() {
+        let mono_6_12 = Version::new(6, 12, 0, 0);
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_class(&mut memory, "", "Test");
+        let root_data = Address::new(0x3000);
+        let other_data = Address::new(0x4000);
+        let root_vtable =
+            write_vtable(&mut memory, address, mono_6_12, &[root_data]);
+        let other_vtable =
+            write_vtable(&mut memory, address, mono_6_12, &[other_data]);
+        write_runtime_info(&mut memory, address, &[root_vtable, other_vtable]);
+
+        let mut runtime = MonoRuntime::new(mono_6_12);
+        let class = Class::read(&mut memory, address, &runtime).unwrap();
+        assert_eq!(class.domain_id(), MONO_ROOT_DOMAIN);
+        assert_eq!(class.static_field_data, Some(root_data));
+
+        runtime.domain_id = 1;
+        let class = Class::read(&mut memory, address, &runtime).unwrap();
+        assert_eq!(class.domain_id(), 1);
+        assert_eq!(class.static_field_data, Some(other_data));
+    }
+
+    #[test]
+    fn class_internals_layout_matches_mono_x86_64() {
+        assert_versioned_layout::<ClassInternals, X86_64>(
+            DEFAULT_MONO_VERSION,
+            ClassInternals::offset_of::<X86_64>,
+            &[
+                ("element_class", 0),
+                ("cast_class", 8),
+                ("supertypes", 16),
+                ("idepth", 24),
+                ("rank", 26),
+                ("class_kind", 27),
+                ("instance_size", 28),
+                ("bitfields_1", 32),
+                ("min_align", 33),
+                ("bitfields_2", 34),
+                ("bitfields_3", 35),
+                ("bitfields_4", 36),
+                ("parent", 40),
+                ("nested_in", 48),
+                ("image", 56),
+                ("name", 64),
+                ("name_space", 72),
+                ("type_token", 80),
+                ("vtable_size", 84),
+                ("interface_count", 88),
+                ("interface_id", 92),
+                ("max_interface_id", 96),
+                ("interface_offsets_count", 100),
+                ("interfaces_packed", 104),
+                ("interface_offsets_packed", 112),
+                ("interface_bitmap", 120),
+                ("interfaces", 128),
+                ("sizes", 136),
+                ("fields", 144),
+                ("methods", 152),
+                ("this_arg", 160),
+                ("byval_arg", 176),
+                ("gc_descr", 192),
+                ("runtime_info", 200),
+                ("vtable", 208),
+                ("infrequent_data", 216),
+                ("unity_user_data", 224),
+                ("flags", 232),
+                ("first_method_idx", 236),
+                ("first_field_idx", 240),
+                ("method_count", 244),
+                ("field_count", 248),
+                ("next_class_cache", 256),
+            ],
+            264,
+        );
+    }
+
+    #[test]
+    fn class_internals_layout_matches_mono_x86() {
+        assert_versioned_layout::<ClassInternals, X86>(
+            DEFAULT_MONO_VERSION,
+            ClassInternals::offset_of::<X86>,
+            &[
+                ("element_class", 0),
+                ("cast_class", 4),
+                ("supertypes", 8),
+                ("idepth", 12),
+                ("rank", 14),
+                ("class_kind", 15),
+                ("instance_size", 16),
+                ("bitfields_1", 20),
+                ("min_align", 21),
+                ("bitfields_2", 22),
+                ("bitfields_3", 23),
+                ("bitfields_4", 24),
+                ("parent", 28),
+                ("nested_in", 32),
+                ("image", 36),
+                ("name", 40),
+                ("name_space", 44),
+                ("type_token", 48),
+                ("vtable_size", 52),
+                ("interface_count", 56),
+                ("interface_id", 60),
+                ("max_interface_id", 64),
+                ("interface_offsets_count", 68),
+                ("interfaces_packed", 72),
+                ("interface_offsets_packed", 76),
+                ("interface_bitmap", 80),
+                ("interfaces", 84),
+                ("sizes", 88),
+                ("fields", 92),
+                ("methods", 96),
+                ("this_arg", 100),
+                ("byval_arg", 108),
+                ("gc_descr", 116),
+                ("runtime_info", 120),
+                ("vtable", 124),
+                ("infrequent_data", 128),
+                ("unity_user_data", 132),
+                ("flags", 136),
+                ("first_method_idx", 140),
+                ("first_field_idx", 144),
+                ("method_count", 148),
+                ("field_count", 152),
+                ("next_class_cache", 156),
+            ],
+            160,
+        );
     }
 }
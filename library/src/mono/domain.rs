@@ -0,0 +1,319 @@
+// See mono/metadata/domain-internals.h and mono/metadata/metadata-internals.h
+// in Mono
+
+use std::collections::LinkedList;
+
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{
+    ArrayPtr, Deserialize, Eager, LazyDeserialize, PrettyPrint, Ptr,
+};
+use crate::memory::{Address, MemoryReader};
+
+use super::images::MonoMutex;
+
+/// The ID of the domain that Mono creates at startup. Other domains are
+/// created by the application.
+pub const MONO_ROOT_DOMAIN: usize = 0;
+
+/// The start of an assembly loaded into a [`MonoDomain`].
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoAssembly {
+    pub ref_count: i32,
+    pub basedir: Option<String>,
+    /// The simple name of the assembly, e.g. `Assembly-CSharp`.
+    pub name: String,
+}
+
+/// The start of an application domain. Each domain has its own copy of the
+/// static fields of every class.
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoDomain {
+    #[cfg_attr(
+        feature = "json",
+        serde(serialize_with = "crate::deserialize::serialize_array")
+    )]
+    pub lock: MonoMutex,
+    pub mp: Option<Address>,
+    pub code_mp: Option<Address>,
+    pub setup: Option<Address>,
+    pub domain: Option<Address>,
+    pub default_context: Option<Address>,
+    pub out_of_memory_ex: Option<Address>,
+    pub null_reference_ex: Option<Address>,
+    pub stack_overflow_ex: Option<Address>,
+    pub typeof_void: Option<Address>,
+    pub ephemeron_tombstone: Option<Address>,
+    pub empty_types: Option<Address>,
+    pub empty_string: Option<Address>,
+    pub env: Option<Address>,
+    pub ldstr_table: Option<Address>,
+    pub type_hash: Option<Address>,
+    pub refobject_hash: Option<Address>,
+    pub type_init_exception_hash: Option<Address>,
+    pub delegate_hash_table: Option<Address>,
+    pub state: u32,
+    pub domain_id: i32,
+    pub shadow_serial: i32,
+    pub domain_assemblies: Option<Ptr<LinkedList<Eager<Ptr<MonoAssembly>>>>>,
+    pub entry_assembly: Option<Ptr<MonoAssembly>>,
+    pub friendly_name: Option<String>,
+}
+
+impl MonoDomain {
+    /// Reads the assemblies that have been loaded into this domain, in load
+    /// order.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the assembly list cannot be
+    /// read.
+    pub fn assemblies<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Vec<MonoAssembly>, DeserializeError> {
+        let Some(ref domain_assemblies) = self.domain_assemblies else {
+            return Ok(Vec::new());
+        };
+        Ok(domain_assemblies
+            .deref(reader)?
+            .into_iter()
+            .map(|assembly| assembly.value)
+            .collect())
+    }
+
+    /// Checks whether an assembly named `name` has been loaded into this
+    /// domain.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the assembly list cannot be
+    /// read.
+    pub fn contains_assembly<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+    ) -> Result<bool, DeserializeError> {
+        Ok(self
+            .assemblies(reader)?
+            .iter()
+            .any(|assembly| assembly.name == name))
+    }
+}
+
+/// The addresses of the global variables through which Mono keeps track of
+/// its app domains (see mono/metadata/domain.c in Mono). These are static, so
+/// where they are depends on the build of the Mono library.
+#[derive(Clone, Copy, Debug)]
+pub struct MonoDomainGlobals {
+    /// The address of `mono_root_domain`, which points to the root domain.
+    pub root_domain: Address,
+    /// The address of `appdomains_list`, which points to an array of the
+    /// domains indexed by domain ID, with null entries for unused IDs.
+    pub appdomains_list: Address,
+    /// The address of `appdomain_list_size`, the number of entries in
+    /// `appdomains_list`.
+    pub appdomain_list_size: Address,
+}
+
+/// The app domains of the target process, read from Mono's list of domains.
+#[derive(PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct AppDomains {
+    // The root domain is always first, since it has the lowest ID.
+    domains: Vec<MonoDomain>,
+}
+
+impl AppDomains {
+    /// Reads the root domain and every domain that has not been unloaded
+    /// from the global variables at `globals`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if a domain cannot be read, the
+    /// root domain is not in the list, or a domain is listed under another
+    /// domain's ID.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        globals: &MonoDomainGlobals,
+    ) -> Result<Self, DeserializeError> {
+        let root_domain =
+            Ptr::<MonoDomain>::deserialize(reader, globals.root_domain)?;
+        let list_size = u16::deserialize(reader, globals.appdomain_list_size)?;
+        let list = Option::<ArrayPtr<Option<Ptr<MonoDomain>>>>::deserialize(
+            reader,
+            globals.appdomains_list,
+        )?;
+        let entries = match list {
+            Some(list) => list.deref(reader, list_size.into())?,
+            None => Vec::new(),
+        };
+
+        let root_entry = entries.get(MONO_ROOT_DOMAIN).copied().flatten();
+        if root_entry.map(|ptr| ptr.address()) != Some(root_domain.address()) {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Root domain at {} is not listed with ID [{MONO_ROOT_DOMAIN}]",
+                root_domain.address()
+            )));
+        }
+        let mut domains = Vec::new();
+        for (domain_id, entry) in entries.into_iter().enumerate() {
+            let Some(entry) = entry else {
+                continue;
+            };
+            let domain = entry.deref(reader)?;
+            if usize::try_from(domain.domain_id).ok() != Some(domain_id) {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Domain at {} has ID [{}] but is listed with ID \
+                     [{domain_id}]",
+                    entry.address(),
+                    domain.domain_id
+                )));
+            }
+            domains.push(domain);
+        }
+
+        Ok(Self { domains })
+    }
+
+    /// Gets the domain that Mono creates at startup.
+    #[must_use]
+    pub fn root_domain(&self) -> &MonoDomain {
+        &self.domains[MONO_ROOT_DOMAIN]
+    }
+
+    /// Iterates over the domains in order of domain ID, including the root
+    /// domain.
+    pub fn domains(&self) -> impl Iterator<Item = &MonoDomain> {
+        self.domains.iter()
+    }
+
+    /// Finds the first domain, in order of domain ID, into which an assembly
+    /// named `assembly_name` has been loaded.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the assemblies of a domain
+    /// cannot be read.
+    pub fn domain_with_assembly<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        assembly_name: &str,
+    ) -> Result<Option<&MonoDomain>, DeserializeError> {
+        for domain in &self.domains {
+            if domain.contains_assembly(reader, assembly_name)? {
+                return Ok(Some(domain));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{Architecture, X86_64};
+    use crate::mono::testing::offset_of;
+
+    use super::*;
+
+    // Writes a domain with ID `domain_id` into which the assemblies named in
+    // `assemblies` have been loaded, and returns its address.
+    fn write_domain(
+        memory: &mut TestMemory,
+        domain_id: i32,
+        assemblies: &[&str],
+    ) -> Address {
+        let offset = offset_of::<MonoDomain>;
+        let address = memory.alloc(MonoDomain::num_bytes::<X86_64>());
+        memory.write_bytes(
+            address + offset("domain_id"),
+            &domain_id.to_le_bytes(),
+        );
+        let mut next = None;
+        for name in assemblies.iter().rev() {
+            let name = memory.alloc_c_string(name);
+            let assembly = memory.alloc(MonoAssembly::num_bytes::<X86_64>());
+            memory
+                .write_ptr(assembly + offset_of::<MonoAssembly>("name"), name);
+            // Each node of the list holds a value and a pointer to the next.
+            let node = memory.alloc(2 * X86_64::POINTER_WIDTH);
+            memory.write_ptr(node, assembly);
+            if let Some(next) = next {
+                memory.write_ptr(node + X86_64::POINTER_WIDTH, next);
+            }
+            next = Some(node);
+        }
+        if let Some(head) = next {
+            memory.write_ptr(address + offset("domain_assemblies"), head);
+        }
+        address
+    }
+
+    // Writes Mono's domain globals, with `root_domain` as the root domain and
+    // `domains` as the list of domains.
+    fn write_globals(
+        memory: &mut TestMemory,
+        root_domain: Address,
+        domains: &[Option<Address>],
+    ) -> MonoDomainGlobals {
+        let list = memory.alloc(domains.len() * X86_64::POINTER_WIDTH);
+        for (i, domain) in domains.iter().enumerate() {
+            if let Some(domain) = domain {
+                memory.write_ptr(list + i * X86_64::POINTER_WIDTH, *domain);
+            }
+        }
+        let globals = MonoDomainGlobals {
+            root_domain: memory.alloc(X86_64::POINTER_WIDTH),
+            appdomains_list: memory.alloc(X86_64::POINTER_WIDTH),
+            appdomain_list_size: memory.alloc(size_of::<u16>()),
+        };
+        memory.write_ptr(globals.root_domain, root_domain);
+        memory.write_ptr(globals.appdomains_list, list);
+        memory.write_bytes(
+            globals.appdomain_list_size,
+            &u16::try_from(domains.len()).unwrap().to_le_bytes(),
+        );
+        globals
+    }
+
+    #[test]
+    fn reads_listed_domains_in_id_order() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let root = write_domain(&mut memory, 0, &["mscorlib"]);
+        let game =
+            write_domain(&mut memory, 2, &["mscorlib", "Assembly-CSharp"]);
+        // The domain with ID 1 has been unloaded.
+        let globals =
+            write_globals(&mut memory, root, &[Some(root), None, Some(game)]);
+
+        let app_domains = AppDomains::read(&mut memory, &globals).unwrap();
+        assert_eq!(app_domains.root_domain().domain_id, 0);
+        let ids = app_domains
+            .domains()
+            .map(|domain| domain.domain_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [0, 2]);
+
+        let find = |memory: &mut TestMemory, name| {
+            app_domains
+                .domain_with_assembly(memory, name)
+                .unwrap()
+                .map(|domain| domain.domain_id)
+        };
+        assert_eq!(find(&mut memory, "mscorlib"), Some(0));
+        assert_eq!(find(&mut memory, "Assembly-CSharp"), Some(2));
+        assert_eq!(find(&mut memory, "Mod"), None);
+    }
+
+    #[test]
+    fn rejects_inconsistent_domain_list() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let root = write_domain(&mut memory, 0, &[]);
+        let other = write_domain(&mut memory, 1, &[]);
+
+        let root_not_first =
+            write_globals(&mut memory, root, &[Some(other), Some(root)]);
+        assert!(AppDomains::read(&mut memory, &root_not_first).is_err());
+
+        let wrong_id =
+            write_globals(&mut memory, root, &[Some(root), None, Some(other)]);
+        assert!(AppDomains::read(&mut memory, &wrong_id).is_err());
+
+        let empty = write_globals(&mut memory, root, &[]);
+        assert!(AppDomains::read(&mut memory, &empty).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{Deserialize, Ptr, RemoteRef, Version};
+use crate::memory::MemoryReader;
+
+use super::{ClassInternals, MonoType, MonoTypeData, MonoTypeKind, Object};
+
+/// Trait for types that the value of a Mono field can be read as.
+pub trait MonoFieldType: Deserialize {
+    /// Returns whether a field of type `typ` in a target with runtime version
+    /// `version` can be read as this type.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the type cannot be decoded.
+    fn matches<M: MemoryReader>(
+        reader: &mut M,
+        typ: &MonoType,
+        version: Version,
+    ) -> Result<bool, DeserializeError>;
+}
+
+/// Trait for structs that have the same layout as a Mono value type when it
+/// is stored inline (i.e. without an object header).
+///
+/// Fields of the value type named [`NAME`](MonoValueType::NAME) in the
+/// namespace [`NAMESPACE`](MonoValueType::NAMESPACE) can be read as the
+/// struct, and boxed instances can be read with
+/// [`Object::unbox`](super::Object::unbox). Fields of other value types can be
+/// nested in the struct as long as those also implement [`Deserialize`].
+pub trait MonoValueType: Deserialize {
+    const NAMESPACE: &'static str;
+    const NAME: &'static str;
+}
+
+impl<T: MonoValueType> MonoFieldType for T {
+    fn matches<M: MemoryReader>(
+        reader: &mut M,
+        typ: &MonoType,
+        version: Version,
+    ) -> Result<bool, DeserializeError> {
+        if typ.kind()? != MonoTypeKind::ValueType {
+            return Ok(false);
+        }
+        let MonoTypeData::Class(class) = typ.resolve_data()? else {
+            return Ok(false);
+        };
+        let class = RemoteRef::<ClassInternals>::new(class.address());
+        Ok(class.name_space(reader, version)? == T::NAMESPACE
+            && class.name(reader, version)? == T::NAME)
+    }
+}
+
+macro_rules! mono_field_type_impl {
+    ($T:ty, $($kind:ident)|+) => {
+        impl MonoFieldType for $T {
+            fn matches<M: MemoryReader>(
+                _reader: &mut M,
+                typ: &MonoType,
+                _version: Version,
+            ) -> Result<bool, DeserializeError> {
+                Ok(matches!(typ.kind()?, $(MonoTypeKind::$kind)|+))
+            }
+        }
+    };
+}
+
+mono_field_type_impl!(bool, Boolean);
+mono_field_type_impl!(i8, I1);
+mono_field_type_impl!(u8, U1);
+mono_field_type_impl!(i16, I2);
+mono_field_type_impl!(u16, U2 | Char);
+mono_field_type_impl!(i32, I4);
+mono_field_type_impl!(u32, U4);
+mono_field_type_impl!(i64, I8);
+mono_field_type_impl!(u64, U8);
+mono_field_type_impl!(f32, R4);
+mono_field_type_impl!(f64, R8);
+mono_field_type_impl!(isize, I);
+mono_field_type_impl!(usize, U);
+
+// Fields of reference types hold pointers to objects.
+impl MonoFieldType for Ptr<Object> {
+    fn matches<M: MemoryReader>(
+        reader: &mut M,
+        typ: &MonoType,
+        version: Version,
+    ) -> Result<bool, DeserializeError> {
+        is_reference(reader, typ, version)
+    }
+}
+
+impl MonoFieldType for Option<Ptr<Object>> {
+    fn matches<M: MemoryReader>(
+        reader: &mut M,
+        typ: &MonoType,
+        version: Version,
+    ) -> Result<bool, DeserializeError> {
+        is_reference(reader, typ, version)
+    }
+}
+
+// Returns whether values of type `typ` are stored as references to objects.
+// Instances of generic classes are references unless the generic class is a
+// value type (e.g. `Nullable<T>` or `KeyValuePair<TKey, TValue>`).
+fn is_reference<M: MemoryReader>(
+    reader: &mut M,
+    typ: &MonoType,
+    version: Version,
+) -> Result<bool, DeserializeError> {
+    let kind = typ.kind()?;
+    if kind != MonoTypeKind::GenericInst {
+        return Ok(kind.is_reference());
+    }
+    let MonoTypeData::GenericClass(generic_class) = typ.resolve_data()? else {
+        return Ok(false);
+    };
+    let container_class = generic_class.container_class(reader)?;
+    let container_class =
+        ClassInternals::read(reader, container_class.address(), version)?;
+    Ok(!container_class.valuetype())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{Address, X86_64};
+    use crate::mono::testing::{class_offset, offset_of, write_class};
+    use crate::mono::MonoGenericClass;
+    use crate::mono::DEFAULT_MONO_VERSION;
+
+    use super::*;
+
+    // Writes a type of kind `typ` whose data pointer is `data`, and reads it
+    // back.
+    fn read_type(
+        memory: &mut TestMemory,
+        typ: u8,
+        data: Option<Address>,
+    ) -> MonoType {
+        let address = memory.alloc(MonoType::num_bytes::<X86_64>());
+        if let Some(data) = data {
+            memory.write_ptr(address + offset_of::<MonoType>("data"), data);
+        }
+        memory.write_bytes(address + offset_of::<MonoType>("typ"), &[typ]);
+        MonoType::deserialize(memory, address).unwrap()
+    }
+
+    // Reads the type of an instance of a generic class named `name`, which is
+    // a value type if `is_value_type` is set.
+    fn read_generic_inst_type(
+        memory: &mut TestMemory,
+        name: &str,
+        is_value_type: bool,
+    ) -> MonoType {
+        let container_class = write_class(memory, "System", name);
+        if is_value_type {
+            memory.write_bytes(
+                container_class + class_offset("bitfields_1"),
+                &[0b100],
+            );
+        }
+        let generic_class =
+            memory.alloc(MonoGenericClass::num_bytes::<X86_64>());
+        memory.write_ptr(
+            generic_class + offset_of::<MonoGenericClass>("container_class"),
+            container_class,
+        );
+        // MONO_TYPE_GENERICINST
+        read_type(memory, 0x15, Some(generic_class))
+    }
+
+    fn matches_object<M: MemoryReader>(reader: &mut M, typ: &MonoType) -> bool {
+        let matches =
+            Ptr::<Object>::matches(reader, typ, DEFAULT_MONO_VERSION).unwrap();
+        assert_eq!(
+            Option::<Ptr<Object>>::matches(reader, typ, DEFAULT_MONO_VERSION)
+                .unwrap(),
+            matches
+        );
+        matches
+    }
+
+    #[test]
+    fn matches_objects_with_reference_types() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let class = write_class(&mut memory, "Game", "Player");
+        // MONO_TYPE_STRING, MONO_TYPE_CLASS
+        let string = read_type(&mut memory, 0x0e, None);
+        let player = read_type(&mut memory, 0x12, Some(class));
+        let list = read_generic_inst_type(&mut memory, "List`1", false);
+
+        assert!(matches_object(&mut memory, &string));
+        assert!(matches_object(&mut memory, &player));
+        assert!(matches_object(&mut memory, &list));
+    }
+
+    #[test]
+    fn does_not_match_objects_with_value_types() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let class = write_class(&mut memory, "UnityEngine", "Vector2");
+        // MONO_TYPE_I4, MONO_TYPE_VALUETYPE
+        let int = read_type(&mut memory, 0x08, None);
+        let vector = read_type(&mut memory, 0x11, Some(class));
+        let nullable = read_generic_inst_type(&mut memory, "Nullable`1", true);
+
+        assert!(!matches_object(&mut memory, &int));
+        assert!(!matches_object(&mut memory, &vector));
+        assert!(!matches_object(&mut memory, &nullable));
+        assert!(i32::matches(&mut memory, &int, DEFAULT_MONO_VERSION).unwrap());
+    }
+}
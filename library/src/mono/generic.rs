@@ -0,0 +1,108 @@
+// See mono/metadata/class-internals.h in Mono
+
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{
+    Bitfield, Deserialize, Eager, LazyDeserialize, PrettyPrint, Ptr,
+    ZeroLengthArray,
+};
+use crate::memory::{Address, MemoryReader};
+
+use super::{Class, MonoType};
+
+/// A list of type arguments, shared between all generic instances that use
+/// the same arguments.
+#[derive(Bitfield, Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoGenericInst {
+    pub id: u32,
+    #[bitfield(pub type_argc: u32 = 0..22, pub is_open: bool = 22)]
+    bitfield: u32,
+    pub type_argv: ZeroLengthArray<Eager<Ptr<MonoType>>>,
+}
+
+/// An instantiation of a generic class with specific type arguments.
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[deserialize(remote)]
+pub struct MonoGenericClass {
+    pub container_class: Ptr<Class>,
+    pub class_inst: Ptr<MonoGenericInst>,
+    pub method_inst: Option<Address>,
+    _bitfields: u32,
+    pub cached_class: Option<Ptr<Class>>,
+    pub owner: Option<Address>,
+}
+
+impl MonoGenericClass {
+    /// Reads the type arguments of this instantiation, in order.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the type arguments cannot be
+    /// read.
+    pub fn type_arguments<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<Vec<MonoType>, DeserializeError> {
+        let class_inst = self.class_inst.deref(reader)?;
+        (0..class_inst.type_argc().try_into()?)
+            .map(|i| {
+                class_inst
+                    .type_argv
+                    .nth_element(reader, i)
+                    .map(|typ| typ.value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{Architecture, X86_64};
+    use crate::mono::testing::{offset_of, write_class};
+
+    use super::*;
+
+    #[test]
+    fn decodes_type_argument_count_and_openness() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let string_type = memory.alloc(MonoType::num_bytes::<X86_64>());
+        memory.write_bytes(string_type + offset_of::<MonoType>("typ"), &[0x0e]);
+        let inst = memory.alloc(
+            MonoGenericInst::num_bytes::<X86_64>() + 3 * X86_64::POINTER_WIDTH,
+        );
+        let offset = offset_of::<MonoGenericInst>;
+        memory.write_bytes(inst + offset("id"), &7_u32.to_le_bytes());
+        // type_argc is the low 22 bits and is_open the bit above them; the
+        // bit above is_open belongs to neither.
+        let bitfield: u32 = 3 | (1 << 22) | (1 << 23);
+        memory.write_bytes(inst + offset("bitfield"), &bitfield.to_le_bytes());
+        for i in 0..3 {
+            memory.write_ptr(
+                inst + offset("type_argv") + i * X86_64::POINTER_WIDTH,
+                string_type,
+            );
+        }
+
+        let generic_inst =
+            MonoGenericInst::deserialize(&mut memory, inst).unwrap();
+        assert_eq!(generic_inst.id, 7);
+        assert_eq!(generic_inst.type_argc(), 3);
+        assert!(generic_inst.is_open());
+
+        let container_class = write_class(&mut memory, "System", "Tuple`3");
+        let class = memory.alloc(MonoGenericClass::num_bytes::<X86_64>());
+        memory.write_ptr(
+            class + offset_of::<MonoGenericClass>("container_class"),
+            container_class,
+        );
+        memory.write_ptr(
+            class + offset_of::<MonoGenericClass>("class_inst"),
+            inst,
+        );
+        let generic_class =
+            MonoGenericClass::deserialize(&mut memory, class).unwrap();
+        let type_arguments = generic_class.type_arguments(&mut memory).unwrap();
+        assert_eq!(type_arguments.len(), 3);
+        assert!(type_arguments.iter().all(|typ| typ.typ == 0x0e));
+    }
+}
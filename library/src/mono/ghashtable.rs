@@ -5,8 +5,14 @@
 use core::borrow::Borrow;
 
 use std::collections::LinkedList;
+use std::fmt;
 
-use crate::deserialize::{Deserialize, Eager, PostSizedArray, Ptr};
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{
+    Deserialize, Eager, LazyDeserialize, PathSegment, PostSizedArray,
+    PrettyPrint, Ptr, Validate, DEFAULT_MAX_DEPTH,
+};
+use crate::memory::{Address, Architecture, MemoryReader};
 
 use super::Hash as MonoHash;
 
@@ -20,29 +26,108 @@ type Slot<K, V> = LinkedList<KeyValuePair<K, V>>;
 
 type SlotPtr<K, V> = Option<Ptr<Slot<K, V>>>;
 
-type SlotArray<K, V> = Eager<PostSizedArray<Eager<SlotPtr<K, V>>, i32>>;
+type SlotArray<K, V> = PostSizedArray<Eager<SlotPtr<K, V>>, i32>;
+
+// Mono never grows a table anywhere near this many slots; a larger size means
+// the header is garbage, and reading the slots would read a huge range.
+const MAX_SIZE: i32 = 1 << 24;
 
 #[derive(Deserialize)]
-pub struct GHashTable<K: Deserialize + MonoHash + Eq, V: Deserialize> {
+#[deserialize(validate)]
+pub(super) struct RawGHashTable<K: Deserialize + MonoHash + Eq, V: Deserialize>
+{
     _hash_func: usize,
     _key_equal_func: usize,
     table: SlotArray<K, V>,
-    _in_use: i32,
+    in_use: i32,
     _threshold: i32,
     _last_rehash: i32,
     _value_destroy_func: usize,
     _key_destroy_func: usize,
 }
 
+pub struct GHashTable<K: Deserialize + MonoHash + Eq, V: Deserialize> {
+    table: Vec<Eager<SlotPtr<K, V>>>,
+    len: usize,
+}
+
+impl<K: Deserialize + MonoHash + Eq, V: Deserialize> Deserialize
+    for GHashTable<K, V>
+{
+    fn num_bytes<A: Architecture>() -> usize {
+        RawGHashTable::<K, V>::num_bytes::<A>()
+    }
+
+    fn alignment<A: Architecture>() -> usize {
+        RawGHashTable::<K, V>::alignment::<A>()
+    }
+
+    fn deserialize<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+    ) -> Result<Self, DeserializeError> {
+        // The header is validated before the slots are read, so that a
+        // garbage size is rejected without reading that many slots.
+        let raw = RawGHashTable::<K, V>::deserialize(reader, address)?;
+        let table = raw.table.deref(reader).map_err(|error| {
+            error.within(PathSegment::Field {
+                struct_name: "GHashTable",
+                field_name: "table",
+                address: raw.table.address(),
+            })
+        })?;
+        Ok(Self {
+            table,
+            len: raw.in_use.try_into()?,
+        })
+    }
+}
+
 impl<K: Deserialize + MonoHash + Eq, V: Deserialize> GHashTable<K, V> {
+    fn pairs(&self) -> impl Iterator<Item = &KeyValuePair<K, V>> {
+        self.table
+            .iter()
+            .filter_map(|slot| slot.value.as_ref())
+            .flatten()
+    }
+
+    /// Iterates over the key-value pairs in this table, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.pairs().map(|pair| (&pair.key, &pair.value))
+    }
+
+    /// Iterates over the keys in this table, in slot order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.pairs().map(|pair| &pair.key)
+    }
+
+    /// Iterates over the values in this table, in slot order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.pairs().map(|pair| &pair.value)
+    }
+
+    /// Gets the number of entries in this table, as counted by Mono when the
+    /// table was read.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether this table has no entries, as counted by Mono when the
+    /// table was read.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     #[must_use]
     pub fn get<B>(&self, key: &B) -> Option<&V>
     where
         K: Borrow<B>,
         B: MonoHash + Eq + ?Sized,
     {
-        let bucket = (key.hash() as usize) % self.table.value.len();
-        let maybe_slot = &self.table.value[bucket].value;
+        let bucket = (key.hash() as usize) % self.table.len();
+        let maybe_slot = &self.table[bucket].value;
         match maybe_slot {
             None => None,
             Some(slot) => {
@@ -57,3 +142,172 @@ impl<K: Deserialize + MonoHash + Eq, V: Deserialize> GHashTable<K, V> {
         }
     }
 }
+
+impl<K: Deserialize + MonoHash + Eq, V: Deserialize> Validate
+    for RawGHashTable<K, V>
+{
+    fn validate(&self, _address: Address) -> Result<(), DeserializeError> {
+        let size = self.table.size();
+        if size <= 0 || size > MAX_SIZE {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "GHashTable size [{size}] is invalid"
+            )));
+        }
+        if self.in_use < 0 {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "GHashTable entry count [{}] is negative",
+                self.in_use
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<K, V> PrettyPrint for GHashTable<K, V>
+where
+    K: Deserialize + MonoHash + Eq + PrettyPrint,
+    V: Deserialize + PrettyPrint,
+{
+    fn pretty_print(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> fmt::Result {
+        let pairs = self.pairs();
+        if depth == 0 {
+            return write!(f, "GHashTable {{.. {} entries}}", pairs.count());
+        }
+        f.debug_map()
+            .entries(pairs.map(|pair| {
+                (pair.key.pretty(depth - 1), pair.value.pretty(depth - 1))
+            }))
+            .finish()
+    }
+}
+
+impl<K, V> fmt::Debug for GHashTable<K, V>
+where
+    K: Deserialize + MonoHash + Eq + PrettyPrint,
+    V: Deserialize + PrettyPrint,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.pretty_print(f, DEFAULT_MAX_DEPTH)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<K, V> serde::Serialize for GHashTable<K, V>
+where
+    K: Deserialize + MonoHash + Eq + serde::Serialize,
+    V: Deserialize + serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+    use crate::mono::testing::{offset_of, write_ghash_table};
+
+    use super::*;
+
+    #[test]
+    fn finds_values_by_key() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let pairs = [("Player", 1), ("Enemy", 2), ("Camera", 3)];
+        let address = write_ghash_table(&mut memory, 2, &pairs);
+        let table =
+            GHashTable::<String, u64>::deserialize(&mut memory, address)
+                .unwrap();
+
+        for (key, value) in pairs {
+            assert_eq!(table.get(key), Some(&value));
+        }
+        assert_eq!(table.get("Door"), None);
+    }
+
+    #[test]
+    fn iterates_over_every_entry() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let pairs = [("Player", 1), ("Enemy", 2), ("Camera", 3)];
+        let address = write_ghash_table(&mut memory, 2, &pairs);
+        let table =
+            GHashTable::<String, u64>::deserialize(&mut memory, address)
+                .unwrap();
+
+        let mut entries: Vec<_> = table
+            .iter()
+            .map(|(key, value)| (key.as_str(), *value))
+            .collect();
+        entries.sort_unstable();
+        assert_eq!(entries, [("Camera", 3), ("Enemy", 2), ("Player", 1)]);
+        assert_eq!(table.keys().count(), 3);
+        assert_eq!(table.values().sum::<u64>(), 6);
+        assert_eq!(table.len(), 3);
+        assert!(!table.is_empty());
+    }
+
+    #[test]
+    fn counts_entries_from_header() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_ghash_table(&mut memory, 2, &[("Player", 1)]);
+        memory.write_bytes(
+            address + offset_of::<RawGHashTable<String, u64>>("in_use"),
+            &5_i32.to_le_bytes(),
+        );
+        let table =
+            GHashTable::<String, u64>::deserialize(&mut memory, address)
+                .unwrap();
+
+        // The count is taken from the header without walking the slots.
+        assert_eq!(table.len(), 5);
+        assert!(!table.is_empty());
+        assert_eq!(table.iter().count(), 1);
+    }
+
+    #[test]
+    fn reads_empty_table() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_ghash_table(&mut memory, 3, &[]);
+        let table =
+            GHashTable::<String, u64>::deserialize(&mut memory, address)
+                .unwrap();
+
+        assert!(table.is_empty());
+        assert_eq!(table.get("Player"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_header_without_reading_slots() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let huge = write_ghash_table(&mut memory, 1, &[]);
+        memory.write_bytes(
+            huge + offset_of::<RawGHashTable<String, u64>>("table") + 8,
+            &i32::MAX.to_le_bytes(),
+        );
+        let negative = write_ghash_table(&mut memory, 1, &[]);
+        memory.write_bytes(
+            negative + offset_of::<RawGHashTable<String, u64>>("in_use"),
+            &(-1_i32).to_le_bytes(),
+        );
+
+        for address in [huge, negative] {
+            let num_reads = memory.num_reads();
+            let error =
+                GHashTable::<String, u64>::deserialize(&mut memory, address)
+                    .err()
+                    .unwrap();
+            assert!(matches!(
+                error.root_cause(),
+                DeserializeError::InvalidStateError(_)
+            ));
+            assert_eq!(memory.num_reads(), num_reads + 1);
+        }
+    }
+}
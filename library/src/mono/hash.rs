@@ -28,3 +28,10 @@ impl Hash for String {
         self.as_str().hash()
     }
 }
+
+// Mono hashes integer keys with g_direct_hash, which uses the key itself.
+impl Hash for u32 {
+    fn hash(&self) -> u32 {
+        *self
+    }
+}
@@ -1,16 +1,23 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::io;
 
 use log::debug;
 
 use crate::deserialize::Error as DeserializeError;
-use crate::deserialize::{Deserialize, Eager, Ptr};
+use crate::deserialize::{
+    read_c_string, Bitfield, Deserialize, Eager, PathSegment, PrettyPrint, Ptr,
+    RemoteRef, Version, VersionedDeserialize,
+};
 use crate::memory::{
     Address, MemoryLocator, MemoryReader, MemorySearcher,
     VariableLengthAddressRange,
 };
 
-use super::utils::has_flag;
-use super::{Class, GHashTable, MonoInternalHashTable};
+use super::{
+    Class, ClassInternals, GHashTable, MonoInternalHashTable, MonoRuntime,
+    MONO_TOKEN_TYPE_DEF,
+};
 
 const MONO_LIBRARY_NAME: &str = "libmonobdwgc-2.0.dylib";
 const LOADED_IMAGES_OFFSET: usize = 0x0016_d638 + 0x0018_e978 + 0x10;
@@ -20,43 +27,155 @@ const SIZE_OF_MONO_MUTEX: usize = 64;
 type GHashTablePtr<K, V> = Eager<Ptr<GHashTable<K, V>>>;
 type MaybeGHashTablePtr<K, V> = Eager<Option<Ptr<GHashTable<K, V>>>>;
 
-type MonoMutex = [u8; SIZE_OF_MONO_MUTEX];
+pub(super) type MonoMutex = [u8; SIZE_OF_MONO_MUTEX];
 type MonoWrapperCaches = [Option<Address>; 21];
 
-#[derive(Debug, Deserialize)]
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct MonoStreamHeader {
     pub data: Option<Address>,
     pub size: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Bitfield, Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct MonoTableInfo {
     pub base: Option<Address>,
+    #[bitfield(pub rows: u32 = 0..24, pub row_size: u32 = 24..32)]
     rows_fields: u32,
     pub size_bitfield: u32,
 }
 
 impl MonoTableInfo {
+    /// Gets the number of bytes in column `column` of each row of this table.
+    /// This follows `mono_metadata_table_size`.
     #[must_use]
-    pub fn rows(&self) -> u32 {
-        self.rows_fields >> 24
+    pub fn column_size(&self, column: usize) -> usize {
+        ((self.size_bitfield >> (column * 2)) & 0x3) as usize + 1
     }
 
+    /// Gets the offset of column `column` from the start of each row of this
+    /// table.
     #[must_use]
-    pub fn row_size(&self) -> u32 {
-        self.rows_fields & 0b1111_1111
+    pub fn column_offset(&self, column: usize) -> usize {
+        (0..column).map(|i| self.column_size(i)).sum()
+    }
+
+    /// Reads the value in column `column` of the row at index `row` (starting
+    /// from 0) of this table. This follows `mono_metadata_decode_row_col`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the table has no such row or
+    /// the value cannot be read.
+    pub fn read_cell<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        row: usize,
+        column: usize,
+    ) -> Result<u32, DeserializeError> {
+        let num_rows: usize = self.rows().try_into()?;
+        let base = match self.base {
+            Some(base) if row < num_rows => base,
+            _ => {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Row {row} does not exist in metadata table with \
+                     {num_rows} rows"
+                )))
+            }
+        };
+
+        let row_size: usize = self.row_size().try_into()?;
+        let bytes = reader.read_vec(VariableLengthAddressRange {
+            start: base + row * row_size + self.column_offset(column),
+            num_bytes: self.column_size(column),
+        })?;
+        // Metadata is always stored in little-endian order.
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0, |value, byte| (value << 8) | u32::from(*byte)))
     }
 }
 
 const MONO_TABLE_NUM: usize = 56;
+const MONO_TABLE_TYPEDEF: usize = 2;
+const MONO_TABLE_FIELD: usize = 4;
+const MONO_TABLE_CONSTANT: usize = 11;
+
+// Columns of the TypeDef table (see ECMA-335 II.22.37).
+const MONO_TYPEDEF_NAME: usize = 1;
+const MONO_TYPEDEF_NAMESPACE: usize = 2;
+const MONO_TYPEDEF_FIELD_LIST: usize = 4;
+
+// Columns of the Field table (see ECMA-335 II.22.15).
+const MONO_FIELD_NAME: usize = 1;
+
+// Columns of the Constant table (see ECMA-335 II.22.9). Mono splits the
+// two-byte Type column into the type and a padding byte.
+const MONO_CONSTANT_TYPE: usize = 0;
+const MONO_CONSTANT_PARENT: usize = 2;
+const MONO_CONSTANT_VALUE: usize = 3;
 
-#[derive(Deserialize)]
+// The Parent column of the Constant table is a HasConstant coded index, whose
+// low bits hold the table the index refers to (see ECMA-335 II.24.2.6).
+const MONO_HASCONSTANT_BITS: usize = 2;
+const MONO_HASCONSTANT_FIELDDEF: usize = 0;
+
+// The element type of string constants (MONO_TYPE_STRING in Mono).
+const MONO_CONSTANT_TYPE_STRING: u32 = 0x0e;
+
+/// A lightweight handle to a class defined in an [`Image`], as listed by
+/// [`LoadedImage::classes`].
+#[derive(Clone, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct ClassHandle {
+    pub name: String,
+    pub namespace: String,
+    pub token: u32,
+    /// The address of the class, or `None` if Mono has not loaded it yet.
+    pub address: Option<Address>,
+}
+
+impl ClassHandle {
+    /// Reads the class this handle refers to from a target whose runtime is
+    /// described by `runtime`, returning `None` if Mono has not loaded it yet.
+    pub fn load<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        runtime: &MonoRuntime,
+    ) -> Result<Option<Class>, DeserializeError> {
+        self.address
+            .map(|address| Class::read(reader, address, runtime))
+            .transpose()
+    }
+}
+
+#[derive(Bitfield, PrettyPrint, VersionedDeserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct Image {
     pub ref_count: i32,
     pub raw_data_handle: Option<Address>,
     pub raw_data: Option<Address>,
     pub raw_data_len: u32,
-    bitfields: [u8; 2],
+    #[bitfield(
+        pub raw_buffer_used: bool = 0,
+        pub raw_data_allocated: bool = 1,
+        pub fileio_used: bool = 2,
+        pub dynamic: bool = 3,
+        pub ref_only: bool = 4,
+        pub uncompressed_metadata: bool = 5,
+        pub metadata_only: bool = 6,
+        pub load_from_context: bool = 7,
+    )]
+    bitfields_1: u8,
+    #[bitfield(
+        pub checked_module_cctor: bool = 0,
+        pub has_module_cctor: bool = 1,
+        pub idx_string_wide: bool = 2,
+        pub idx_guid_wide: bool = 3,
+        pub idx_blob_wide: bool = 4,
+        pub core_clr_platform_code: bool = 5,
+    )]
+    bitfields_2: u8,
     pub name: String,
     pub assembly_name: Option<String>,
     pub module_name: Option<String>,
@@ -76,6 +195,10 @@ pub struct Image {
     pub tables_base: Option<Address>,
     pub referenced_tables: u64,
     pub referenced_table_rows: Option<Address>,
+    #[cfg_attr(
+        feature = "json",
+        serde(serialize_with = "crate::deserialize::serialize_array")
+    )]
     pub tables: [MonoTableInfo; MONO_TABLE_NUM],
     pub references: Option<Address>,
     pub nreferences: i32,
@@ -88,17 +211,21 @@ pub struct Image {
     pub aotid: [u8; 16],
     pub assembly: Option<Address>,
     pub method_cache: Option<Address>,
-    pub class_cache: MonoInternalHashTable<Eager<Ptr<Class>>>,
+    pub class_cache: MonoInternalHashTable<Class>,
     pub methodref_cache: Option<Address>,
     pub field_cache: Option<Address>,
     pub typespec_cache: Option<Address>,
     pub memberref_signatures: Option<Address>,
     pub helper_signatures: Option<Address>,
     pub method_signatures: Option<Address>,
-    pub name_cache: MaybeGHashTablePtr<String, GHashTablePtr<String, u64>>,
+    pub name_cache: MaybeGHashTablePtr<String, GHashTablePtr<String, usize>>,
     pub array_cache: Option<Address>,
     pub ptr_cache: Option<Address>,
     pub szarray_cache: Option<Address>,
+    #[cfg_attr(
+        feature = "json",
+        serde(serialize_with = "crate::deserialize::serialize_array")
+    )]
     pub szarray_cache_lock: MonoMutex,
     pub native_func_wrapper_cache: Option<Address>,
     pub runtime_invoke_vcall_cache: Option<Address>,
@@ -133,82 +260,478 @@ pub struct Image {
     pub anonymous_generic_method_container: Option<Address>,
     pub weak_fields_inited: bool,
     pub weak_field_indexes: Option<Address>,
+    #[cfg_attr(
+        feature = "json",
+        serde(serialize_with = "crate::deserialize::serialize_array")
+    )]
     pub lock: MonoMutex,
 }
 
+/// An [`Image`] together with the address it was read from, as listed by
+/// [`LoadedImages`].
+#[derive(PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct LoadedImage {
+    pub address: Address,
+    pub image: Image,
+}
+
 impl Image {
-    #[must_use]
-    pub fn raw_buffer_used(&self) -> bool {
-        has_flag(self.bitfields[0], 0b1000_0000)
+    /// Reads the image at `address`, laid out according to the runtime
+    /// version `version`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the image cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        version: Version,
+    ) -> Result<Self, DeserializeError> {
+        Self::deserialize_versioned(reader, address, version)
     }
+}
 
-    #[must_use]
-    pub fn raw_data_allocated(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0100_0000)
+impl LoadedImage {
+    /// Reads the image at `address`, laid out according to the runtime
+    /// version `version`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the image cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        version: Version,
+    ) -> Result<Self, DeserializeError> {
+        let image = Image::read(reader, address, version).map_err(|error| {
+            error.within(PathSegment::Field {
+                struct_name: "LoadedImage",
+                field_name: "image",
+                address,
+            })
+        })?;
+        Ok(Self { address, image })
     }
 
-    #[must_use]
-    pub fn fileio_used(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0010_0000)
+    /// Iterates over the namespaces that contain classes defined in this
+    /// image, according to its name cache. Top-level classes without a
+    /// namespace are in the namespace `""`.
+    ///
+    /// Yields nothing if the name cache has not been initialized.
+    pub fn namespaces(&self) -> impl Iterator<Item = &str> {
+        self.image
+            .name_cache
+            .value
+            .iter()
+            .flat_map(|name_cache| name_cache.keys())
+            .map(String::as_str)
     }
 
-    #[must_use]
-    pub fn dynamic(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0001_0000)
+    /// Lists the classes defined in this image, in order of token.
+    ///
+    /// Classes that Mono has loaded are read from the image's class cache,
+    /// laid out according to `runtime`'s version. Classes that have not been
+    /// loaded yet are read from the TypeDef metadata table and have no
+    /// address.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the class cache or the
+    /// metadata cannot be read.
+    pub fn classes<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        runtime: &MonoRuntime,
+    ) -> Result<Vec<ClassHandle>, DeserializeError> {
+        let version = runtime.version;
+        let mut classes = BTreeMap::new();
+
+        let cached_classes = self
+            .image
+            .class_cache
+            .iter(reader, version)
+            .collect::<Result<Vec<_>, _>>()?;
+        for ptr in cached_classes {
+            self.check_owns_class(reader, ptr.address(), version)?;
+            let class = RemoteRef::<ClassInternals>::new(ptr.address());
+            let token = class.type_token(reader, version)?;
+            classes.insert(
+                token,
+                ClassHandle {
+                    name: class.name(reader, version)?,
+                    namespace: class.name_space(reader, version)?,
+                    token,
+                    address: Some(ptr.address()),
+                },
+            );
+        }
+
+        let type_defs = &self.image.tables[MONO_TABLE_TYPEDEF];
+        for row in 0..type_defs.rows().try_into()? {
+            // TypeDef tokens are numbered from 1.
+            let token = u32::try_from(MONO_TOKEN_TYPE_DEF + row + 1)?;
+            if let Entry::Vacant(entry) = classes.entry(token) {
+                let name_index =
+                    type_defs.read_cell(reader, row, MONO_TYPEDEF_NAME)?;
+                let namespace_index =
+                    type_defs.read_cell(reader, row, MONO_TYPEDEF_NAMESPACE)?;
+                entry.insert(ClassHandle {
+                    name: self.read_heap_string(reader, name_index)?,
+                    namespace: self
+                        .read_heap_string(reader, namespace_index)?,
+                    token,
+                    address: None,
+                });
+            }
+        }
+
+        Ok(classes.into_values().collect())
     }
 
-    #[must_use]
-    pub fn ref_only(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0000_1000)
+    /// Finds the class named `name` in the namespace `namespace` that is
+    /// defined in this image and reads it from a target whose runtime is
+    /// described by `runtime`, returning `None` if there is no such class.
+    ///
+    /// Nested classes are named by the path of enclosing classes from the
+    /// outermost one, separated by `/` (e.g. `Outer/Inner`); `namespace` is
+    /// the namespace of the outermost class. Nested classes can only be found
+    /// once Mono has set up the nested classes of their enclosing classes.
+    ///
+    /// Returns an [`Error`](DeserializeError) if this image's caches cannot
+    /// be read, if the outermost class exists but has not been loaded, or if
+    /// the nested classes of an enclosing class have not been set up.
+    pub fn find_class<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        namespace: &str,
+        name: &str,
+        runtime: &MonoRuntime,
+    ) -> Result<Option<Class>, DeserializeError> {
+        let mut names = name.split('/');
+        let outermost_name = names.next().unwrap_or_default();
+        let version = runtime.version;
+        let Some(mut class_addr) = self.find_top_level_class(
+            reader,
+            namespace,
+            outermost_name,
+            version,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        for nested_name in names {
+            let Some(nested_addr) = self.find_nested_class(
+                reader,
+                class_addr,
+                nested_name,
+                version,
+            )?
+            else {
+                return Ok(None);
+            };
+            class_addr = nested_addr;
+        }
+
+        self.check_owns_class(reader, class_addr, version)?;
+        Class::read(reader, class_addr, runtime).map(Some)
     }
 
-    #[must_use]
-    pub fn uncompressed_metadata(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0000_0100)
+    // Checks that the class at `class_addr`, which was found in this image's
+    // caches, points back to this image.
+    fn check_owns_class<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        class_addr: Address,
+        version: Version,
+    ) -> Result<(), DeserializeError> {
+        let image = RemoteRef::<ClassInternals>::new(class_addr)
+            .image(reader, version)?;
+        match image {
+            Some(ptr) if ptr.address() == self.address => Ok(()),
+            Some(ptr) => Err(DeserializeError::InvalidStateError(format!(
+                "Class at {class_addr} in image \"{}\" points to image at {} \
+                 instead of {}",
+                self.image.name,
+                ptr.address(),
+                self.address
+            ))),
+            None => Err(DeserializeError::InvalidStateError(format!(
+                "Class at {class_addr} in image \"{}\" has no image",
+                self.image.name
+            ))),
+        }
     }
 
-    #[must_use]
-    pub fn metadata_only(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0000_0010)
+    // Finds the address of a class that is not nested in another class using
+    // the image's name cache.
+    fn find_top_level_class<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        namespace: &str,
+        name: &str,
+        version: Version,
+    ) -> Result<Option<Address>, DeserializeError> {
+        let name_cache =
+            self.image.name_cache.value.as_ref().ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Name cache of image \"{}\" has not been initialized",
+                    self.image.name
+                ))
+            })?;
+        let Some(namespace_cache) = name_cache.get(namespace) else {
+            return Ok(None);
+        };
+        let Some(&type_index) = namespace_cache.value.get(name) else {
+            return Ok(None);
+        };
+
+        let type_def_token = MONO_TOKEN_TYPE_DEF + type_index;
+        match self.find_cached_class(reader, type_def_token, version)? {
+            Some(class_addr) => Ok(Some(class_addr)),
+            None => {
+                let full_name = if namespace.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{namespace}.{name}")
+                };
+                Err(DeserializeError::InvalidStateError(format!(
+                    "Class \"{full_name}\" (token 0x{type_def_token:x}) has \
+                     not been loaded"
+                )))
+            }
+        }
     }
 
-    #[must_use]
-    pub fn load_from_context(&self) -> bool {
-        has_flag(self.bitfields[0], 0b0000_0001)
+    /// Reads the value of the string constant `field` declared by the class
+    /// named `name` in the namespace `namespace` from this image's metadata,
+    /// returning `None` if there is no such constant.
+    ///
+    /// The class does not need to have been loaded, but nested classes are
+    /// not supported.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the metadata cannot be read
+    /// or the constant is not a string.
+    pub fn read_string_constant<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        namespace: &str,
+        name: &str,
+        field: &str,
+    ) -> Result<Option<String>, DeserializeError> {
+        let Some(type_def) = self.find_type_def(reader, namespace, name)?
+        else {
+            return Ok(None);
+        };
+        let Some(field_def) = self.find_field_def(reader, type_def, field)?
+        else {
+            return Ok(None);
+        };
+        let Some(constant) = self.find_constant(reader, field_def)? else {
+            return Ok(None);
+        };
+
+        let constants = &self.image.tables[MONO_TABLE_CONSTANT];
+        let typ = constants.read_cell(reader, constant, MONO_CONSTANT_TYPE)?;
+        if typ != MONO_CONSTANT_TYPE_STRING {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Constant {name}.{field} in image \"{}\" has type 0x{typ:02x} \
+                 instead of string",
+                self.image.name
+            )));
+        }
+        let value_index =
+            constants.read_cell(reader, constant, MONO_CONSTANT_VALUE)?;
+        let bytes = self.read_blob(reader, value_index)?;
+        // String constants are stored as UTF-16 without a terminator.
+        let chars = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+        String::from_utf16(&chars).map(Some).map_err(|_| {
+            DeserializeError::InvalidStateError(format!(
+                "Constant {name}.{field} in image \"{}\" is not valid UTF-16",
+                self.image.name
+            ))
+        })
     }
 
-    #[must_use]
-    pub fn checked_module_cctor(&self) -> bool {
-        has_flag(self.bitfields[1], 0b1000_0000)
+    // Finds the row of the TypeDef table that defines the class named `name`
+    // in the namespace `namespace`.
+    fn find_type_def<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<usize>, DeserializeError> {
+        let type_defs = &self.image.tables[MONO_TABLE_TYPEDEF];
+        for row in 0..type_defs.rows().try_into()? {
+            let name_index =
+                type_defs.read_cell(reader, row, MONO_TYPEDEF_NAME)?;
+            if self.read_heap_string(reader, name_index)? != name {
+                continue;
+            }
+            let namespace_index =
+                type_defs.read_cell(reader, row, MONO_TYPEDEF_NAMESPACE)?;
+            if self.read_heap_string(reader, namespace_index)? == namespace {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
     }
 
-    #[must_use]
-    pub fn has_module_cctor(&self) -> bool {
-        has_flag(self.bitfields[1], 0b0100_0000)
+    // Finds the row of the Field table that defines the field named `name`
+    // of the class defined in row `type_def` of the TypeDef table.
+    fn find_field_def<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        type_def: usize,
+        name: &str,
+    ) -> Result<Option<usize>, DeserializeError> {
+        let type_defs = &self.image.tables[MONO_TABLE_TYPEDEF];
+        let fields = &self.image.tables[MONO_TABLE_FIELD];
+        // The fields of a class run from its field list (an index starting
+        // from 1) to the field list of the next class, or to the end of the
+        // Field table for the last class.
+        let first: usize = type_defs
+            .read_cell(reader, type_def, MONO_TYPEDEF_FIELD_LIST)?
+            .try_into()?;
+        let end: usize = if type_def + 1 < type_defs.rows().try_into()? {
+            type_defs
+                .read_cell(reader, type_def + 1, MONO_TYPEDEF_FIELD_LIST)?
+                .try_into()?
+        } else {
+            usize::try_from(fields.rows())? + 1
+        };
+
+        for row in first.saturating_sub(1)..end.saturating_sub(1) {
+            let name_index = fields.read_cell(reader, row, MONO_FIELD_NAME)?;
+            if self.read_heap_string(reader, name_index)? == name {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
     }
 
-    #[must_use]
-    pub fn idx_string_wide(&self) -> bool {
-        has_flag(self.bitfields[1], 0b0010_0000)
+    // Finds the row of the Constant table that holds the value of the field
+    // defined in row `field_def` of the Field table.
+    fn find_constant<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        field_def: usize,
+    ) -> Result<Option<usize>, DeserializeError> {
+        let constants = &self.image.tables[MONO_TABLE_CONSTANT];
+        let parent = u32::try_from(
+            ((field_def + 1) << MONO_HASCONSTANT_BITS)
+                | MONO_HASCONSTANT_FIELDDEF,
+        )?;
+        for row in 0..constants.rows().try_into()? {
+            if constants.read_cell(reader, row, MONO_CONSTANT_PARENT)? == parent
+            {
+                return Ok(Some(row));
+            }
+        }
+        Ok(None)
     }
 
-    #[must_use]
-    pub fn idx_guid_wide(&self) -> bool {
-        has_flag(self.bitfields[1], 0b0001_0000)
+    // Reads the blob at `index` in the image's blob heap. Blobs are prefixed
+    // with their length, compressed as in `mono_metadata_decode_blob_size`
+    // (see ECMA-335 II.24.2.4).
+    fn read_blob<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        index: u32,
+    ) -> Result<Vec<u8>, DeserializeError> {
+        let heap_error = || {
+            DeserializeError::InvalidStateError(format!(
+                "Blob heap of image \"{}\" does not contain a blob at index \
+                 {index}",
+                self.image.name
+            ))
+        };
+        let index: usize = index.try_into()?;
+        let heap_size: usize = self.image.heap_blob.size.try_into()?;
+        let data = match self.image.heap_blob.data {
+            Some(data) if index < heap_size => data + index,
+            _ => return Err(heap_error()),
+        };
+
+        let first = u8::deserialize(reader, data)?;
+        let (header_size, length_mask) = if first & 0x80 == 0 {
+            (1, 0x7f)
+        } else if first & 0xc0 == 0x80 {
+            (2, 0x3f)
+        } else if first & 0xe0 == 0xc0 {
+            (4, 0x1f)
+        } else {
+            return Err(heap_error());
+        };
+        let header = reader.read_vec(VariableLengthAddressRange {
+            start: data,
+            num_bytes: header_size,
+        })?;
+        let length = header[1..]
+            .iter()
+            .fold(usize::from(first & length_mask), |length, byte| {
+                (length << 8) | usize::from(*byte)
+            });
+        if header_size + length > heap_size - index {
+            return Err(heap_error());
+        }
+        Ok(reader.read_vec(VariableLengthAddressRange {
+            start: data + header_size,
+            num_bytes: length,
+        })?)
     }
 
-    #[must_use]
-    pub fn idx_blob_wide(&self) -> bool {
-        has_flag(self.bitfields[1], 0b0000_1000)
+    // Reads the string at `index` in the image's string heap.
+    fn read_heap_string<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        index: u32,
+    ) -> Result<String, DeserializeError> {
+        match self.image.heap_strings.data {
+            Some(data) if index < self.image.heap_strings.size => {
+                read_c_string(reader, data + index.try_into()?)
+            }
+            _ => Err(DeserializeError::InvalidStateError(format!(
+                "String heap of image \"{}\" does not contain index {index}",
+                self.image.name
+            ))),
+        }
     }
 
-    #[must_use]
-    pub fn core_clr_platform_code(&self) -> bool {
-        has_flag(self.bitfields[1], 0b0000_0100)
+    // Finds the address of the class with the type definition token
+    // `type_def_token` in the image's class cache, if it has been loaded.
+    fn find_cached_class<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        type_def_token: usize,
+        version: Version,
+    ) -> Result<Option<Address>, DeserializeError> {
+        Ok(self
+            .image
+            .class_cache
+            .get(reader, &type_def_token.try_into()?, version)?
+            .map(|ptr| ptr.address()))
+    }
+
+    // Finds the address of the class named `name` that is nested directly in
+    // the class at `outer_addr` using the outer class's nested classes.
+    fn find_nested_class<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        outer_addr: Address,
+        name: &str,
+        version: Version,
+    ) -> Result<Option<Address>, DeserializeError> {
+        let outer = ClassInternals::read(reader, outer_addr, version)?;
+        for ptr in outer.nested_classes(reader)? {
+            let class = RemoteRef::<ClassInternals>::new(ptr.address());
+            if class.name(reader, version)? == name {
+                return Ok(Some(ptr.address()));
+            }
+        }
+        Ok(None)
     }
 }
 
-type ImageHashTable = GHashTablePtr<String, Eager<Ptr<Image>>>;
+type ImageHashTable = GHashTablePtr<String, Ptr<LoadedImage>>;
 
 const MONO_TEXT_SECTION_PATTERN: [u8; 48] = [
     0xcf, 0xfa, 0xed, 0xfe, 0x07, 0x00, 0x00, 0x01, 0x03, 0x00, 0x00, 0x00,
@@ -222,6 +745,8 @@ const SEARCHER: MemorySearcher =
 
 const MAX_OFFSET_BYTES: usize = 1024 * 4096;
 
+#[derive(PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct LoadedImages {
     loaded_images_by_name: ImageHashTable,
 }
@@ -241,9 +766,7 @@ impl LoadedImages {
                     num_bytes: MAX_OFFSET_BYTES,
                 },
             )?
-            .ok_or_else(|| {
-                io::Error::new(io::ErrorKind::Other, "TEXT signature not found")
-            })?;
+            .ok_or_else(|| io::Error::other("TEXT signature not found"))?;
         debug!("TEXT addr is {}", text_addr);
 
         let addr = text_addr + LOADED_IMAGES_OFFSET;
@@ -253,9 +776,523 @@ impl LoadedImages {
         })
     }
 
-    #[must_use]
-    pub fn get_image(&self, name: &str) -> Option<&Image> {
-        let eager_ptr = self.loaded_images_by_name.value.get(name)?;
-        Some(&eager_ptr.value)
+    /// Iterates over the names of the loaded images.
+    pub fn image_names(&self) -> impl Iterator<Item = &str> {
+        self.loaded_images_by_name.value.keys().map(String::as_str)
+    }
+
+    /// Reads the loaded image named `name`, laid out according to the runtime
+    /// version `version`, returning `None` if no such image is loaded.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the image cannot be read.
+    pub fn get_image<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+        version: Version,
+    ) -> Result<Option<LoadedImage>, DeserializeError> {
+        self.loaded_images_by_name
+            .value
+            .get(name)
+            .map(|ptr| LoadedImage::read(reader, ptr.address(), version))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{X86, X86_64};
+    use crate::mono::testing::{
+        assert_versioned_layout, cache_class, class_offset, image_offset,
+        offset_of, write_class, write_heap, write_image, write_name_cache,
+        write_nested_classes, write_table,
+    };
+    use crate::mono::DEFAULT_MONO_VERSION;
+
+    use super::*;
+
+    #[test]
+    fn table_info_decodes_rows_from_low_bits() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address =
+            memory.alloc(<MonoTableInfo as Deserialize>::num_bytes::<X86_64>());
+        memory.write_bytes(
+            address + offset_of::<MonoTableInfo>("rows_fields"),
+            &0x0e01_2345_u32.to_le_bytes(),
+        );
+
+        let table = MonoTableInfo::deserialize(&mut memory, address).unwrap();
+        assert_eq!(table.rows(), 0x01_2345);
+        assert_eq!(table.row_size(), 0x0e);
+    }
+
+    #[test]
+    fn image_decodes_flags_in_declaration_order() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        memory
+            .write_bytes(address + image_offset("bitfields_1"), &[0b1010_0101]);
+        memory
+            .write_bytes(address + image_offset("bitfields_2"), &[0b0001_0110]);
+
+        let image =
+            Image::read(&mut memory, address, DEFAULT_MONO_VERSION).unwrap();
+        assert_eq!(image.name, "Test");
+        assert!(image.raw_buffer_used());
+        assert!(!image.raw_data_allocated());
+        assert!(image.fileio_used());
+        assert!(!image.dynamic());
+        assert!(!image.ref_only());
+        assert!(image.uncompressed_metadata());
+        assert!(!image.metadata_only());
+        assert!(image.load_from_context());
+        assert!(!image.checked_module_cctor());
+        assert!(image.has_module_cctor());
+        assert!(image.idx_string_wide());
+        assert!(!image.idx_guid_wide());
+        assert!(image.idx_blob_wide());
+        assert!(!image.core_clr_platform_code());
+    }
+
+    // Encodes `string` as a string constant in the blob heap, prefixed with
+    // its compressed length.
+    fn string_blob(string: &str) -> Vec<u8> {
+        let bytes = string
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let mut blob = if bytes.len() < 0x80 {
+            vec![bytes.len() as u8]
+        } else {
+            vec![0x80 | (bytes.len() >> 8) as u8, bytes.len() as u8]
+        };
+        blob.extend(bytes);
+        blob
+    }
+
+    // Writes an image defining the classes `Other` and `Consts`, which both
+    // declare a string constant named `MonoVersion` with the given values.
+    fn write_constants_image(
+        memory: &mut TestMemory,
+        other_value: &str,
+        consts_value: &str,
+    ) -> Address {
+        let image = write_image(memory, "mscorlib");
+        write_heap(
+            memory,
+            image,
+            "heap_strings",
+            b"\0Other\0Consts\0MonoVersion\0Unrelated\0",
+        );
+        let other_blob = string_blob(other_value);
+        let mut blobs = vec![0];
+        blobs.extend(&other_blob);
+        blobs.extend(string_blob(consts_value));
+        write_heap(memory, image, "heap_blob", &blobs);
+
+        // Flags, Name, Namespace, Extends, FieldList, MethodList
+        write_table(
+            memory,
+            image,
+            MONO_TABLE_TYPEDEF,
+            &[4, 2, 2, 2, 2, 2],
+            &[&[0, 1, 0, 0, 1, 1], &[0, 7, 0, 0, 2, 1]],
+        );
+        // Flags, Name, Signature
+        write_table(
+            memory,
+            image,
+            MONO_TABLE_FIELD,
+            &[2, 2, 2],
+            &[&[0, 14, 0], &[0, 26, 0], &[0, 14, 0]],
+        );
+        // Type, Padding, Parent, Value
+        let consts_blob = 1 + other_blob.len() as u32;
+        write_table(
+            memory,
+            image,
+            MONO_TABLE_CONSTANT,
+            &[1, 1, 2, 2],
+            &[&[0x0e, 0, 1 << 2, 1], &[0x0e, 0, 3 << 2, consts_blob]],
+        );
+        image
+    }
+
+    #[test]
+    fn reads_string_constant_of_named_class() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_constants_image(&mut memory, "1.0", "5.11.0.0");
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+
+        let read = |memory: &mut TestMemory, class, field| {
+            image
+                .read_string_constant(memory, "", class, field)
+                .unwrap()
+        };
+        assert_eq!(
+            read(&mut memory, "Consts", "MonoVersion").as_deref(),
+            Some("5.11.0.0")
+        );
+        assert_eq!(
+            read(&mut memory, "Other", "MonoVersion").as_deref(),
+            Some("1.0")
+        );
+        assert_eq!(read(&mut memory, "Consts", "Unrelated"), None);
+        assert_eq!(read(&mut memory, "Missing", "MonoVersion"), None);
+    }
+
+    #[test]
+    fn reads_string_constant_with_long_blob() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let long_value = "6.12.0.199 (2020-02/".repeat(4);
+        let address = write_constants_image(&mut memory, "", &long_value);
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+
+        let value = image
+            .read_string_constant(&mut memory, "", "Consts", "MonoVersion")
+            .unwrap();
+        assert_eq!(value, Some(long_value));
+    }
+
+    // Writes an image whose TypeDef table defines Game.Player, Game.Enemy
+    // and Door, returning its address.
+    fn write_type_defs_image(memory: &mut TestMemory) -> Address {
+        let image = write_image(memory, "Test");
+        write_heap(
+            memory,
+            image,
+            "heap_strings",
+            b"\0Game\0Player\0Enemy\0Door\0",
+        );
+        // Flags, Name, Namespace, Extends, FieldList, MethodList
+        write_table(
+            memory,
+            image,
+            MONO_TABLE_TYPEDEF,
+            &[4, 2, 2, 2, 2, 2],
+            &[
+                &[0x0010_0001, 6, 1, 0, 1, 1],
+                &[0, 13, 1, 0, 1, 1],
+                &[0, 19, 0, 0x1234, 1, 0xbeef],
+            ],
+        );
+        image
+    }
+
+    #[test]
+    fn reads_cells_of_type_def_table() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_type_defs_image(&mut memory);
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+
+        let type_defs = &image.image.tables[MONO_TABLE_TYPEDEF];
+        assert_eq!(type_defs.rows(), 3);
+        assert_eq!(type_defs.row_size(), 14);
+        assert_eq!(type_defs.column_size(0), 4);
+        assert_eq!(type_defs.column_size(5), 2);
+        assert_eq!(type_defs.column_offset(4), 10);
+
+        let mut read =
+            |row, column| type_defs.read_cell(&mut memory, row, column);
+        assert_eq!(read(0, 0).unwrap(), 0x0010_0001);
+        assert_eq!(read(0, MONO_TYPEDEF_NAME).unwrap(), 6);
+        assert_eq!(read(1, MONO_TYPEDEF_NAME).unwrap(), 13);
+        assert_eq!(read(2, MONO_TYPEDEF_NAMESPACE).unwrap(), 0);
+        assert_eq!(read(2, 3).unwrap(), 0x1234);
+        assert_eq!(read(2, 5).unwrap(), 0xbeef);
+        assert!(matches!(
+            read(3, MONO_TYPEDEF_NAME),
+            Err(DeserializeError::InvalidStateError(_))
+        ));
+    }
+
+    #[test]
+    fn lists_uncached_classes_from_type_defs() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_type_defs_image(&mut memory);
+        let enemy = write_class(&mut memory, "Game", "Enemy");
+        cache_class(&mut memory, address, enemy, 0x0200_0002);
+
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        let classes: Vec<_> = image
+            .classes(&mut memory, &MonoRuntime::default())
+            .unwrap()
+            .into_iter()
+            .map(|class| {
+                (class.token, class.namespace, class.name, class.address)
+            })
+            .collect();
+        assert_eq!(
+            classes,
+            [
+                (0x0200_0001, "Game".into(), "Player".into(), None),
+                (0x0200_0002, "Game".into(), "Enemy".into(), Some(enemy)),
+                (0x0200_0003, String::new(), "Door".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn lists_cached_classes() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let player = write_class(&mut memory, "Game", "Player");
+        let enemy = write_class(&mut memory, "Game", "Enemy");
+        cache_class(&mut memory, address, player, 0x0200_0003);
+        cache_class(&mut memory, address, enemy, 0x0200_0002);
+
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        let classes =
+            image.classes(&mut memory, &MonoRuntime::default()).unwrap();
+        let names: Vec<_> = classes
+            .iter()
+            .map(|class| (class.namespace.as_str(), class.name.as_str()))
+            .collect();
+        assert_eq!(names, [("Game", "Enemy"), ("Game", "Player")]);
+        assert_eq!(classes[0].address, Some(enemy));
+    }
+
+    #[test]
+    fn rejects_cached_class_of_other_image() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let other = write_image(&mut memory, "Other");
+        let class = write_class(&mut memory, "Game", "Player");
+        cache_class(&mut memory, address, class, 0x0200_0002);
+        memory.write_ptr(class + class_offset("image"), other);
+
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        let error = image
+            .classes(&mut memory, &MonoRuntime::default())
+            .err()
+            .unwrap();
+        assert!(matches!(
+            error,
+            DeserializeError::InvalidStateError(ref message)
+                if message.contains("points to image")
+        ));
+    }
+
+    // Reads the image at `address` and finds the class `name` in `namespace`.
+    fn find_class(
+        memory: &mut TestMemory,
+        address: Address,
+        namespace: &str,
+        name: &str,
+    ) -> Result<Option<Class>, DeserializeError> {
+        LoadedImage::read(memory, address, DEFAULT_MONO_VERSION)
+            .unwrap()
+            .find_class(memory, namespace, name, &MonoRuntime::default())
+    }
+
+    #[test]
+    fn finds_classes_by_namespace() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let game_player = write_class(&mut memory, "Game", "Player");
+        let ui_player = write_class(&mut memory, "UI", "Player");
+        let door = write_class(&mut memory, "", "Door");
+        cache_class(&mut memory, address, game_player, 0x0200_0002);
+        cache_class(&mut memory, address, ui_player, 0x0200_0003);
+        cache_class(&mut memory, address, door, 0x0200_0004);
+        write_name_cache(
+            &mut memory,
+            address,
+            &[
+                ("Game", &[("Player", 2)]),
+                ("UI", &[("Player", 3)]),
+                ("", &[("Door", 4)]),
+            ],
+        );
+
+        for (namespace, name, class) in [
+            ("Game", "Player", game_player),
+            ("UI", "Player", ui_player),
+            ("", "Door", door),
+        ] {
+            let found = find_class(&mut memory, address, namespace, name)
+                .unwrap()
+                .unwrap();
+            assert_eq!(found.address, class);
+            assert_eq!(found.internals.name_space, namespace);
+        }
+    }
+
+    #[test]
+    fn finds_nested_classes_by_path() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let outer = write_class(&mut memory, "Game", "Outer");
+        let other = write_class(&mut memory, "", "Other");
+        let inner = write_class(&mut memory, "", "Inner");
+        let deep = write_class(&mut memory, "", "Deep");
+        cache_class(&mut memory, address, outer, 0x0200_0002);
+        for class in [other, inner, deep] {
+            memory.write_ptr(class + class_offset("image"), address);
+        }
+        write_nested_classes(&mut memory, outer, &[other, inner]);
+        write_nested_classes(&mut memory, inner, &[deep]);
+        write_name_cache(&mut memory, address, &[("Game", &[("Outer", 2)])]);
+
+        let mut find = |name| {
+            find_class(&mut memory, address, "Game", name)
+                .unwrap()
+                .map(|class| class.address)
+        };
+        assert_eq!(find("Outer/Inner"), Some(inner));
+        assert_eq!(find("Outer/Other"), Some(other));
+        assert_eq!(find("Outer/Inner/Deep"), Some(deep));
+        assert_eq!(find("Outer/Deep"), None);
+        assert_eq!(find("Outer/Inner/Missing"), None);
+    }
+
+    #[test]
+    fn returns_none_for_missing_classes() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let player = write_class(&mut memory, "Game", "Player");
+        cache_class(&mut memory, address, player, 0x0200_0002);
+        write_name_cache(&mut memory, address, &[("Game", &[("Player", 2)])]);
+
+        for (namespace, name) in
+            [("Game", "Enemy"), ("UI", "Player"), ("", "Player")]
+        {
+            assert!(find_class(&mut memory, address, namespace, name)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn reports_classes_that_are_not_loaded() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let outer = write_class(&mut memory, "Game", "Outer");
+        cache_class(&mut memory, address, outer, 0x0200_0002);
+
+        let expect_error = |memory: &mut TestMemory, name, expected| {
+            let error =
+                find_class(memory, address, "Game", name).err().unwrap();
+            assert!(
+                matches!(
+                    error,
+                    DeserializeError::InvalidStateError(ref message)
+                        if message.contains(expected)
+                ),
+                "{error:?}"
+            );
+        };
+        expect_error(&mut memory, "Outer", "has not been initialized");
+
+        write_name_cache(
+            &mut memory,
+            address,
+            &[("Game", &[("Outer", 2), ("Player", 3)])],
+        );
+        expect_error(&mut memory, "Player", "has not been loaded");
+        expect_error(&mut memory, "Outer/Inner", "have not been set up");
+    }
+
+    #[test]
+    fn finds_class_on_x86() {
+        let mut memory = TestMemory::<X86>::new(Address::new(0x1000));
+        let address = write_image(&mut memory, "Test");
+        let player = write_class(&mut memory, "Game", "Player");
+        cache_class(&mut memory, address, player, 0x0200_0002);
+        write_name_cache(&mut memory, address, &[("Game", &[("Player", 2)])]);
+
+        let image =
+            LoadedImage::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        let runtime = MonoRuntime::default();
+        let class = image
+            .find_class(&mut memory, "Game", "Player", &runtime)
+            .unwrap()
+            .unwrap();
+        assert_eq!(class.address, player);
+        assert_eq!(class.internals.name, "Player");
+        assert!(image
+            .find_class(&mut memory, "Game", "Enemy", &runtime)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn image_layout_matches_mono_x86_64() {
+        assert_versioned_layout::<Image, X86_64>(
+            DEFAULT_MONO_VERSION,
+            Image::offset_of::<X86_64>,
+            &[
+                ("ref_count", 0),
+                ("raw_data_len", 24),
+                ("bitfields_1", 28),
+                ("bitfields_2", 29),
+                ("name", 32),
+                ("md_version_major", 64),
+                ("heap_strings", 104),
+                ("heap_blob", 136),
+                ("tables_base", 200),
+                ("referenced_tables", 208),
+                ("tables", 224),
+                ("references", 1120),
+                ("aotid", 1184),
+                ("class_cache", 1216),
+                ("name_cache", 1304),
+                ("szarray_cache_lock", 1336),
+                ("native_func_wrapper_cache", 1400),
+                ("wrapper_caches", 1552),
+                ("var_cache_fast", 1720),
+                ("weak_fields_inited", 1808),
+                ("weak_field_indexes", 1816),
+                ("lock", 1824),
+            ],
+            1888,
+        );
+    }
+
+    #[test]
+    fn image_layout_matches_mono_x86() {
+        assert_versioned_layout::<Image, X86>(
+            DEFAULT_MONO_VERSION,
+            Image::offset_of::<X86>,
+            &[
+                ("ref_count", 0),
+                ("raw_data_len", 12),
+                ("bitfields_1", 16),
+                ("bitfields_2", 17),
+                ("name", 20),
+                ("md_version_major", 36),
+                ("heap_strings", 56),
+                ("heap_blob", 72),
+                ("tables_base", 104),
+                ("referenced_tables", 108),
+                ("tables", 120),
+                ("references", 792),
+                ("aotid", 824),
+                ("class_cache", 848),
+                ("name_cache", 896),
+                ("szarray_cache_lock", 912),
+                ("native_func_wrapper_cache", 976),
+                ("wrapper_caches", 1052),
+                ("var_cache_fast", 1136),
+                ("weak_fields_inited", 1180),
+                ("weak_field_indexes", 1184),
+                ("lock", 1188),
+            ],
+            1252,
+        );
     }
 }
@@ -1,12 +1,345 @@
-use crate::deserialize::{ArrayPtr, Deserialize};
-use crate::memory::Address;
+// See mono/metadata/mono-internal-hash.c in Mono
 
-#[derive(Debug, Deserialize)]
-pub struct MonoInternalHashTable<T: Deserialize> {
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{
+    ArrayPtr, Deserialize, PrettyPrint, Ptr, Validate, Version,
+};
+use crate::memory::{Address, MemoryReader};
+
+use super::Hash as MonoHash;
+
+// Mono grows the table once it holds this many entries per slot (see
+// resize_if_needed in mono/metadata/mono-internal-hash.c).
+const MAX_LOAD_FACTOR: i32 = 3;
+
+/// A value that can be stored in a [`MonoInternalHashTable`].
+///
+/// Mono stores values directly in the table's slots and chains values that
+/// share a slot through a pointer inside each value. The table's
+/// `key_extract` and `next_value` function pointers find the key and the next
+/// value; this trait provides the same operations for the target's values.
+/// Values are read laid out according to the target's runtime version.
+pub trait MonoInternalHashValue: Sized {
+    type Key: MonoHash + Eq;
+
+    /// Reads the key of the value pointed to by `value`, like the table's
+    /// `key_extract` function.
+    fn key<M: MemoryReader>(
+        reader: &mut M,
+        value: &Ptr<Self>,
+        version: Version,
+    ) -> Result<Self::Key, DeserializeError>;
+
+    /// Reads the pointer to the value following `value` in its slot, like the
+    /// table's `next_value` function.
+    fn next_value<M: MemoryReader>(
+        reader: &mut M,
+        value: &Ptr<Self>,
+        version: Version,
+    ) -> Result<Option<Ptr<Self>>, DeserializeError>;
+}
+
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[deserialize(validate)]
+pub struct MonoInternalHashTable<T> {
     pub hash_func: Option<Address>,
     pub key_extract: Option<Address>,
     pub next_value: Option<Address>,
     pub size: i32,
     pub num_entries: i32,
-    pub table: ArrayPtr<T>,
+    pub table: ArrayPtr<Option<Ptr<T>>>,
+}
+
+impl<T: MonoInternalHashValue> MonoInternalHashTable<T> {
+    /// Finds the value with key `key` in a target with runtime version
+    /// `version`, returning `None` if the table does not contain one. This
+    /// follows `mono_internal_hash_table_lookup`.
+    pub fn get<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        key: &T::Key,
+        version: Version,
+    ) -> Result<Option<Ptr<T>>, DeserializeError> {
+        let size: usize = self.size.try_into()?;
+        let mut value =
+            self.table.nth_element(reader, key.hash() as usize % size)?;
+        let mut num_visited = 0;
+        while let Some(ptr) = value {
+            if T::key(reader, &ptr, version)? == *key {
+                return Ok(Some(ptr));
+            }
+            num_visited += 1;
+            self.check_num_visited(num_visited)?;
+            value = T::next_value(reader, &ptr, version)?;
+        }
+        Ok(None)
+    }
+
+    /// Iterates over pointers to every value in the table, in slot order, in
+    /// a target with runtime version `version`.
+    ///
+    /// The iterator stops after yielding the first error.
+    pub fn iter<'a, M: MemoryReader>(
+        &'a self,
+        reader: &'a mut M,
+        version: Version,
+    ) -> Iter<'a, T, M> {
+        Iter {
+            table: self,
+            reader,
+            version,
+            next_slot: 0,
+            next_value: None,
+            num_visited: 0,
+            finished: false,
+        }
+    }
+
+    // Checks that no more values have been visited than the table contains,
+    // which would mean that a chain is corrupt or cyclic.
+    fn check_num_visited(
+        &self,
+        num_visited: usize,
+    ) -> Result<(), DeserializeError> {
+        if num_visited > self.num_entries.try_into()? {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Hash table contains more than {} entries",
+                self.num_entries
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// An iterator over the values in a [`MonoInternalHashTable`], created by
+/// [`MonoInternalHashTable::iter`].
+pub struct Iter<'a, T: MonoInternalHashValue, M: MemoryReader> {
+    table: &'a MonoInternalHashTable<T>,
+    reader: &'a mut M,
+    version: Version,
+    next_slot: usize,
+    next_value: Option<Ptr<T>>,
+    num_visited: usize,
+    finished: bool,
+}
+
+impl<'a, T: MonoInternalHashValue, M: MemoryReader> Iter<'a, T, M> {
+    fn try_next(&mut self) -> Result<Option<Ptr<T>>, DeserializeError> {
+        let size: usize = self.table.size.try_into()?;
+        while self.next_value.is_none() {
+            if self.next_slot >= size {
+                return Ok(None);
+            }
+            self.next_value =
+                self.table.table.nth_element(self.reader, self.next_slot)?;
+            self.next_slot += 1;
+        }
+
+        let Some(value) = self.next_value.take() else {
+            return Ok(None);
+        };
+        self.num_visited += 1;
+        self.table.check_num_visited(self.num_visited)?;
+        self.next_value = T::next_value(self.reader, &value, self.version)?;
+        Ok(Some(value))
+    }
+}
+
+impl<'a, T: MonoInternalHashValue, M: MemoryReader> Iterator
+    for Iter<'a, T, M>
+{
+    type Item = Result<Ptr<T>, DeserializeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.try_next().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+impl<T> Validate for MonoInternalHashTable<T> {
+    fn validate(&self, _address: Address) -> Result<(), DeserializeError> {
+        if self.size <= 0 {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Hash table size [{}] is not positive",
+                self.size
+            )));
+        }
+        if self.num_entries < 0
+            || self.num_entries > self.size.saturating_mul(MAX_LOAD_FACTOR)
+        {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Hash table entry count [{}] is invalid for size [{}]",
+                self.num_entries, self.size
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deserialize::{LazyDeserialize, RemoteRef};
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{Architecture, X86_64};
+    use crate::mono::testing::offset_of;
+    use crate::mono::DEFAULT_MONO_VERSION;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[deserialize(remote)]
+    struct Entry {
+        key: u32,
+        next: Option<Ptr<Entry>>,
+    }
+
+    impl MonoInternalHashValue for Entry {
+        type Key = u32;
+
+        fn key<M: MemoryReader>(
+            reader: &mut M,
+            value: &Ptr<Self>,
+            _version: Version,
+        ) -> Result<u32, DeserializeError> {
+            RemoteRef::<Entry>::from(value).key(reader)
+        }
+
+        fn next_value<M: MemoryReader>(
+            reader: &mut M,
+            value: &Ptr<Self>,
+            _version: Version,
+        ) -> Result<Option<Ptr<Self>>, DeserializeError> {
+            RemoteRef::<Entry>::from(value).next(reader)
+        }
+    }
+
+    // Writes a table with `size` slots holding entries with keys `keys`,
+    // inserting each at the head of its slot like Mono does, and returns the
+    // addresses of the table and the entries.
+    fn write_table(
+        memory: &mut TestMemory,
+        size: i32,
+        keys: &[u32],
+    ) -> (Address, Vec<Address>) {
+        let offset = offset_of::<MonoInternalHashTable<Entry>>;
+        let width = X86_64::POINTER_WIDTH;
+        let slots = memory.alloc(usize::try_from(size).unwrap() * width);
+        let mut heads = vec![None; size.try_into().unwrap()];
+        let entries = keys
+            .iter()
+            .map(|&key| {
+                let entry = memory.alloc(Entry::num_bytes::<X86_64>());
+                let slot = key as usize % heads.len();
+                memory.write_bytes(entry, &key.to_le_bytes());
+                if let Some(head) = heads[slot] {
+                    memory.write_ptr(entry + offset_of::<Entry>("next"), head);
+                }
+                memory.write_ptr(slots + slot * width, entry);
+                heads[slot] = Some(entry);
+                entry
+            })
+            .collect();
+
+        let address =
+            memory.alloc(MonoInternalHashTable::<Entry>::num_bytes::<X86_64>());
+        memory.write_bytes(address + offset("size"), &size.to_le_bytes());
+        memory.write_bytes(
+            address + offset("num_entries"),
+            &i32::try_from(keys.len()).unwrap().to_le_bytes(),
+        );
+        memory.write_ptr(address + offset("table"), slots);
+        (address, entries)
+    }
+
+    fn keys<M: MemoryReader>(
+        reader: &mut M,
+        table: &MonoInternalHashTable<Entry>,
+    ) -> Result<Vec<u32>, DeserializeError> {
+        let values = table
+            .iter(reader, DEFAULT_MONO_VERSION)
+            .collect::<Result<Vec<_>, _>>()?;
+        values
+            .iter()
+            .map(|value| Ok(value.deref(reader)?.key))
+            .collect()
+    }
+
+    #[test]
+    fn finds_values_by_key() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let (address, entries) = write_table(&mut memory, 2, &[1, 2, 3]);
+        let table =
+            MonoInternalHashTable::<Entry>::deserialize(&mut memory, address)
+                .unwrap();
+
+        for (key, entry) in [1, 2, 3].iter().zip(entries) {
+            let value =
+                table.get(&mut memory, key, DEFAULT_MONO_VERSION).unwrap();
+            assert_eq!(value.map(|ptr| ptr.address()), Some(entry));
+        }
+        assert!(table
+            .get(&mut memory, &4, DEFAULT_MONO_VERSION)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn iterates_in_slot_order() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let (address, _) = write_table(&mut memory, 2, &[1, 2, 3]);
+        let table =
+            MonoInternalHashTable::<Entry>::deserialize(&mut memory, address)
+                .unwrap();
+
+        assert_eq!(keys(&mut memory, &table).unwrap(), [2, 3, 1]);
+    }
+
+    #[test]
+    fn stops_at_cyclic_chain() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let (address, entries) = write_table(&mut memory, 2, &[1, 3]);
+        memory.write_ptr(entries[0] + offset_of::<Entry>("next"), entries[1]);
+        let entry = RemoteRef::<Entry>::new(entries[0]).read(&mut memory);
+        assert_eq!(entry.unwrap().next.unwrap().address(), entries[1]);
+        let table =
+            MonoInternalHashTable::<Entry>::deserialize(&mut memory, address)
+                .unwrap();
+
+        assert!(matches!(
+            table.get(&mut memory, &5, DEFAULT_MONO_VERSION),
+            Err(DeserializeError::InvalidStateError(_))
+        ));
+        let mut iter = table.iter(&mut memory, DEFAULT_MONO_VERSION);
+        assert!(matches!(iter.next(), Some(Ok(_))));
+        assert!(matches!(iter.next(), Some(Ok(_))));
+        assert!(matches!(iter.next(), Some(Err(_))));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_sizes() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let (empty, _) = write_table(&mut memory, 0, &[]);
+        let (overfull, _) = write_table(&mut memory, 1, &[1, 2, 3, 4]);
+
+        for address in [empty, overfull] {
+            let error = MonoInternalHashTable::<Entry>::deserialize(
+                &mut memory,
+                address,
+            )
+            .err()
+            .unwrap();
+            assert!(matches!(
+                error.root_cause(),
+                DeserializeError::InvalidStateError(_)
+            ));
+        }
+    }
 }
@@ -0,0 +1,345 @@
+// See mono/metadata/metadata-internals.h and mono/metadata/blob.h in Mono
+
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{Deserialize, PrettyPrint, RemoteRef};
+use crate::memory::Address;
+
+use super::{Class, MonoGenericClass};
+
+// See mono/metadata/tabledefs.h in Mono
+const FIELD_ATTRIBUTE_FIELD_ACCESS_MASK: i16 = 0x0007;
+const FIELD_ATTRIBUTE_STATIC: i16 = 0x0010;
+const FIELD_ATTRIBUTE_INIT_ONLY: i16 = 0x0020;
+const FIELD_ATTRIBUTE_LITERAL: i16 = 0x0040;
+
+/// The kind of a [`MonoType`], corresponding to `MonoTypeEnum` in Mono.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonoTypeKind {
+    End,
+    Void,
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    String,
+    Ptr,
+    ByRef,
+    ValueType,
+    Class,
+    Var,
+    Array,
+    GenericInst,
+    TypedByRef,
+    I,
+    U,
+    FnPtr,
+    Object,
+    SzArray,
+    MVar,
+    CModReqd,
+    CModOpt,
+    Internal,
+    Modifier,
+    Sentinel,
+    Pinned,
+    Enum,
+}
+
+impl MonoTypeKind {
+    /// Returns whether values of this kind are always stored as references to
+    /// objects. Values of `GenericInst` types are references unless the
+    /// generic class is a value type.
+    #[must_use]
+    pub fn is_reference(self) -> bool {
+        matches!(
+            self,
+            Self::String
+                | Self::Class
+                | Self::Array
+                | Self::Object
+                | Self::SzArray
+        )
+    }
+}
+
+impl TryFrom<u8> for MonoTypeKind {
+    type Error = DeserializeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x00 => Self::End,
+            0x01 => Self::Void,
+            0x02 => Self::Boolean,
+            0x03 => Self::Char,
+            0x04 => Self::I1,
+            0x05 => Self::U1,
+            0x06 => Self::I2,
+            0x07 => Self::U2,
+            0x08 => Self::I4,
+            0x09 => Self::U4,
+            0x0a => Self::I8,
+            0x0b => Self::U8,
+            0x0c => Self::R4,
+            0x0d => Self::R8,
+            0x0e => Self::String,
+            0x0f => Self::Ptr,
+            0x10 => Self::ByRef,
+            0x11 => Self::ValueType,
+            0x12 => Self::Class,
+            0x13 => Self::Var,
+            0x14 => Self::Array,
+            0x15 => Self::GenericInst,
+            0x16 => Self::TypedByRef,
+            0x18 => Self::I,
+            0x19 => Self::U,
+            0x1b => Self::FnPtr,
+            0x1c => Self::Object,
+            0x1d => Self::SzArray,
+            0x1e => Self::MVar,
+            0x1f => Self::CModReqd,
+            0x20 => Self::CModOpt,
+            0x21 => Self::Internal,
+            0x40 => Self::Modifier,
+            0x41 => Self::Sentinel,
+            0x45 => Self::Pinned,
+            0x55 => Self::Enum,
+            _ => {
+                return Err(DeserializeError::InvalidStateError(format!(
+                    "Unknown Mono type 0x{value:02x}"
+                )))
+            }
+        })
+    }
+}
+
+/// Who can access a field, as described by its attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldVisibility {
+    CompilerControlled,
+    Private,
+    FamilyAndAssembly,
+    Assembly,
+    Family,
+    FamilyOrAssembly,
+    Public,
+}
+
+/// The value that the `data` pointer of a [`MonoType`] refers to, which
+/// depends on the type's kind.
+#[derive(Clone, Copy, Debug)]
+pub enum MonoTypeData {
+    /// The type has no data (e.g. because it is a primitive type).
+    None,
+
+    /// The class of a `Class` or `ValueType` type.
+    Class(RemoteRef<Class>),
+
+    /// The class of the elements of an `SzArray` type.
+    ElementClass(RemoteRef<Class>),
+
+    /// The generic instance of a `GenericInst` type.
+    GenericClass(RemoteRef<MonoGenericClass>),
+
+    /// The type pointed to by a `Ptr` type.
+    Type(RemoteRef<MonoType>),
+
+    /// Data that is not decoded, e.g. the array type of an `Array` type or the
+    /// generic parameter of a `Var` type.
+    Other(Address),
+}
+
+#[derive(Clone, Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoType {
+    pub data: Option<Address>,
+    pub attrs: i16,
+    pub typ: u8,
+    _bitfields: u8,
+}
+
+impl MonoType {
+    /// Decodes the kind of this type.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the kind is not known.
+    pub fn kind(&self) -> Result<MonoTypeKind, DeserializeError> {
+        MonoTypeKind::try_from(self.typ)
+    }
+
+    /// Decodes what this type's `data` pointer refers to.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the kind is not known or the
+    /// pointer is null for a kind that requires it.
+    pub fn resolve_data(&self) -> Result<MonoTypeData, DeserializeError> {
+        let kind = self.kind()?;
+        let Some(data) = self.data else {
+            return match kind {
+                MonoTypeKind::Class
+                | MonoTypeKind::ValueType
+                | MonoTypeKind::SzArray
+                | MonoTypeKind::GenericInst
+                | MonoTypeKind::Ptr => {
+                    Err(DeserializeError::InvalidStateError(format!(
+                        "Mono type of kind {kind:?} has no data"
+                    )))
+                }
+                _ => Ok(MonoTypeData::None),
+            };
+        };
+
+        Ok(match kind {
+            MonoTypeKind::Class | MonoTypeKind::ValueType => {
+                MonoTypeData::Class(RemoteRef::new(data))
+            }
+            MonoTypeKind::SzArray => {
+                MonoTypeData::ElementClass(RemoteRef::new(data))
+            }
+            MonoTypeKind::GenericInst => {
+                MonoTypeData::GenericClass(RemoteRef::new(data))
+            }
+            MonoTypeKind::Ptr => MonoTypeData::Type(RemoteRef::new(data)),
+            _ => MonoTypeData::Other(data),
+        })
+    }
+
+    /// Returns whether a field of this type is static.
+    #[must_use]
+    pub fn is_static(&self) -> bool {
+        self.attrs & FIELD_ATTRIBUTE_STATIC != 0
+    }
+
+    /// Returns whether a field of this type is a compile-time constant.
+    #[must_use]
+    pub fn is_literal(&self) -> bool {
+        self.attrs & FIELD_ATTRIBUTE_LITERAL != 0
+    }
+
+    /// Returns whether a field of this type can only be assigned during
+    /// initialization.
+    #[must_use]
+    pub fn is_init_only(&self) -> bool {
+        self.attrs & FIELD_ATTRIBUTE_INIT_ONLY != 0
+    }
+
+    /// Decodes the visibility of a field of this type, returning `None` if it
+    /// is not valid.
+    #[must_use]
+    pub fn visibility(&self) -> Option<FieldVisibility> {
+        match self.attrs & FIELD_ATTRIBUTE_FIELD_ACCESS_MASK {
+            0 => Some(FieldVisibility::CompilerControlled),
+            1 => Some(FieldVisibility::Private),
+            2 => Some(FieldVisibility::FamilyAndAssembly),
+            3 => Some(FieldVisibility::Assembly),
+            4 => Some(FieldVisibility::Family),
+            5 => Some(FieldVisibility::FamilyOrAssembly),
+            6 => Some(FieldVisibility::Public),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typ(typ: u8, attrs: i16, data: Option<Address>) -> MonoType {
+        MonoType {
+            data,
+            attrs,
+            typ,
+            _bitfields: 0,
+        }
+    }
+
+    #[test]
+    fn decodes_kinds_of_mono_types() {
+        assert_eq!(MonoTypeKind::try_from(0x00).unwrap(), MonoTypeKind::End);
+        assert_eq!(MonoTypeKind::try_from(0x08).unwrap(), MonoTypeKind::I4);
+        assert_eq!(
+            MonoTypeKind::try_from(0x15).unwrap(),
+            MonoTypeKind::GenericInst
+        );
+        assert_eq!(MonoTypeKind::try_from(0x18).unwrap(), MonoTypeKind::I);
+        assert_eq!(MonoTypeKind::try_from(0x55).unwrap(), MonoTypeKind::Enum);
+        // Values between the ranges Mono uses are not kinds.
+        for value in [0x17, 0x1a, 0x22, 0x56] {
+            assert!(matches!(
+                MonoTypeKind::try_from(value),
+                Err(DeserializeError::InvalidStateError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn only_object_kinds_are_references() {
+        for kind in [
+            MonoTypeKind::String,
+            MonoTypeKind::Class,
+            MonoTypeKind::Array,
+            MonoTypeKind::Object,
+            MonoTypeKind::SzArray,
+        ] {
+            assert!(kind.is_reference(), "{kind:?}");
+        }
+        for kind in [
+            MonoTypeKind::I4,
+            MonoTypeKind::ValueType,
+            MonoTypeKind::GenericInst,
+            MonoTypeKind::Ptr,
+        ] {
+            assert!(!kind.is_reference(), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn decodes_field_attributes() {
+        // Public static readonly
+        let field = typ(0x08, 0x0036, None);
+        assert!(field.is_static());
+        assert!(field.is_init_only());
+        assert!(!field.is_literal());
+        assert_eq!(field.visibility(), Some(FieldVisibility::Public));
+
+        // Private const
+        let constant = typ(0x08, 0x0051, None);
+        assert!(constant.is_static());
+        assert!(constant.is_literal());
+        assert!(!constant.is_init_only());
+        assert_eq!(constant.visibility(), Some(FieldVisibility::Private));
+
+        assert_eq!(
+            typ(0x08, 0x0004, None).visibility(),
+            Some(FieldVisibility::Family)
+        );
+        assert_eq!(typ(0x08, 0x0007, None).visibility(), None);
+    }
+
+    #[test]
+    fn resolves_data_by_kind() {
+        let data = Some(Address::new(0x1000));
+        assert!(matches!(
+            typ(0x12, 0, data).resolve_data().unwrap(),
+            MonoTypeData::Class(_)
+        ));
+        assert!(matches!(
+            typ(0x1d, 0, data).resolve_data().unwrap(),
+            MonoTypeData::ElementClass(_)
+        ));
+        assert!(matches!(
+            typ(0x13, 0, data).resolve_data().unwrap(),
+            MonoTypeData::Other(address) if address == Address::new(0x1000)
+        ));
+        assert!(matches!(
+            typ(0x08, 0, None).resolve_data().unwrap(),
+            MonoTypeData::None
+        ));
+        assert!(typ(0x11, 0, None).resolve_data().is_err());
+    }
+}
@@ -1,15 +1,164 @@
-use crate::deserialize::{Deserialize, Eager, Ptr};
-use crate::memory::Address;
+use std::any::type_name;
 
-use super::{Class, MonoVTable};
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{Deserialize, PathSegment, PrettyPrint, Ptr};
+use crate::memory::{Address, MemoryReader};
 
-#[derive(Deserialize)]
+use super::{
+    Class, MonoFieldType, MonoRuntime, MonoVTable, MonoValue, MonoValueType,
+};
+
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct ObjectInternals {
-    pub vtable: Eager<Ptr<MonoVTable>>,
+    pub vtable: Address,
     pub synchronization: Option<Address>,
 }
 
-pub struct Object<'a> {
+#[derive(PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Object {
+    pub address: Address,
     pub internals: ObjectInternals,
-    pub class: &'a Class,
+    pub vtable: MonoVTable,
+    pub class: Class,
+}
+
+impl Object {
+    /// Reads the object at `address` from a target whose runtime is described
+    /// by `runtime`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the object, its vtable or its
+    /// class cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        runtime: &MonoRuntime,
+    ) -> Result<Self, DeserializeError> {
+        let object_field = |field_name| PathSegment::Field {
+            struct_name: "Object",
+            field_name,
+            address,
+        };
+
+        let internals = ObjectInternals::deserialize(reader, address)
+            .map_err(|error| error.within(object_field("internals")))?;
+        let vtable =
+            MonoVTable::read(reader, internals.vtable, runtime.version)
+                .map_err(|error| error.within(object_field("vtable")))?;
+        let class = Class::read(reader, vtable.class.address(), runtime)
+            .map_err(|error| error.within(object_field("class")))?;
+        Ok(Self {
+            address,
+            internals,
+            vtable,
+            class,
+        })
+    }
+
+    /// Reads the instance field `name` of this object as a `T`.
+    ///
+    /// Fields of reference types can be read as [`Ptr<Object>`] (or
+    /// `Option<Ptr<Object>>` if they may be null), and the object they refer
+    /// to can then be read with [`Object::read`] or
+    /// [`get_field_object`](Object::get_field_object). Fields of value types
+    /// are stored inline and can be read as a struct implementing
+    /// [`MonoValueType`].
+    ///
+    /// Returns an [`Error`](DeserializeError) if the object's class has no
+    /// such field, the field is static, the field's type cannot be read as a
+    /// `T`, or the value cannot be read.
+    pub fn get_field<T: MonoFieldType, M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+    ) -> Result<T, DeserializeError> {
+        let field = self.class.get_field(name)?;
+        let typ = &field.typ.value;
+        if typ.is_static() {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Field \"{name}\" of class \"{}\" is static",
+                self.class.internals.name
+            )));
+        }
+        if !T::matches(reader, typ, self.class.runtime().version)? {
+            let kind = typ.kind()?;
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Field \"{name}\" of class \"{}\" has type {kind:?}, which \
+                 cannot be read as {}",
+                self.class.internals.name,
+                type_name::<T>(),
+            )));
+        }
+
+        // Instance field offsets include the object header.
+        T::deserialize(reader, self.address + field.offset.try_into()?)
+    }
+
+    /// Reads this object as a boxed instance of the value type `T`. The value
+    /// is stored directly after the object header.
+    ///
+    /// Returns an [`Error`](DeserializeError) if this object's class is not
+    /// `T`'s value type, or the value cannot be read.
+    pub fn unbox<T: MonoValueType, M: MemoryReader>(
+        &self,
+        reader: &mut M,
+    ) -> Result<T, DeserializeError> {
+        let internals = &self.class.internals;
+        if internals.name_space != T::NAMESPACE || internals.name != T::NAME {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Object of class \"{}.{}\" cannot be unboxed as {}",
+                internals.name_space,
+                internals.name,
+                type_name::<T>(),
+            )));
+        }
+        T::deserialize(
+            reader,
+            self.address + ObjectInternals::num_bytes::<M::Architecture>(),
+        )
+    }
+
+    /// Reads the instance field `name` of this object as a [`MonoValue`] based
+    /// on the field's type.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the object's class has no
+    /// such field, the field is static, or the value cannot be read.
+    pub fn read_value<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+    ) -> Result<MonoValue, DeserializeError> {
+        let field = self.class.get_field(name)?;
+        let typ = &field.typ.value;
+        if typ.is_static() {
+            return Err(DeserializeError::InvalidStateError(format!(
+                "Field \"{name}\" of class \"{}\" is static",
+                self.class.internals.name
+            )));
+        }
+        MonoValue::read(
+            reader,
+            typ,
+            self.address + field.offset.try_into()?,
+            self.class.runtime(),
+        )
+    }
+
+    /// Reads the object referred to by the instance field `name` of this
+    /// object, returning `None` if the field is null.
+    ///
+    /// Returns an [`Error`](DeserializeError) under the same conditions as
+    /// [`get_field`](Object::get_field).
+    pub fn get_field_object<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        name: &str,
+    ) -> Result<Option<Object>, DeserializeError> {
+        self.get_field::<Option<Ptr<Object>>, M>(reader, name)?
+            .map(|ptr| {
+                Object::read(reader, ptr.address(), self.class.runtime())
+            })
+            .transpose()
+    }
 }
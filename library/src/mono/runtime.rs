@@ -0,0 +1,85 @@
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{PrettyPrint, Version};
+use crate::memory::MemoryReader;
+
+use super::{FieldCache, LoadedImages, MONO_ROOT_DOMAIN};
+
+/// The version reported by Unity's fork of Mono, which is assumed when the
+/// version of the target's runtime is not known.
+pub const DEFAULT_MONO_VERSION: Version = Version::new(5, 11, 0, 0);
+
+const CORLIB_NAME: &str = "mscorlib";
+
+/// Describes the Mono runtime of the target process, which determines how
+/// structures whose layout differs between versions of Mono are read.
+///
+/// The runtime also memoizes the fields of classes read from it, which Mono
+/// does not change once a class is set up. Clones share the same memo.
+#[derive(Clone, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoRuntime {
+    pub version: Version,
+    /// The ID of the domain whose static fields classes read from this
+    /// runtime use.
+    pub domain_id: usize,
+    #[cfg_attr(feature = "json", serde(skip))]
+    field_cache: FieldCache,
+}
+
+impl MonoRuntime {
+    /// Describes a runtime with version `version`, reading static fields from
+    /// the root domain.
+    #[must_use]
+    pub fn new(version: Version) -> Self {
+        Self {
+            version,
+            domain_id: MONO_ROOT_DOMAIN,
+            field_cache: FieldCache::default(),
+        }
+    }
+
+    pub(super) fn field_cache(&self) -> &FieldCache {
+        &self.field_cache
+    }
+
+    /// Detects the runtime of the target process from the `Consts.MonoVersion`
+    /// constant in the metadata of the core library, which must be among
+    /// `loaded_images`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the core library is not
+    /// loaded, the constant cannot be read or it is not a valid version.
+    pub fn detect<M: MemoryReader>(
+        reader: &mut M,
+        loaded_images: &LoadedImages,
+    ) -> Result<Self, DeserializeError> {
+        // The version is not known yet, so the core library is read with the
+        // default layout. This relies on the fields used to find constants
+        // being laid out the same way in every supported version.
+        let corlib = loaded_images
+            .get_image(reader, CORLIB_NAME, DEFAULT_MONO_VERSION)?
+            .ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Image \"{CORLIB_NAME}\" is not loaded"
+                ))
+            })?;
+        let version = corlib
+            .read_string_constant(reader, "", "Consts", "MonoVersion")?
+            .ok_or_else(|| {
+                DeserializeError::InvalidStateError(format!(
+                    "Image \"{CORLIB_NAME}\" has no Consts.MonoVersion constant"
+                ))
+            })?;
+        let version = version.parse().map_err(|error| {
+            DeserializeError::InvalidStateError(format!(
+                "Mono version \"{version}\" is invalid: {error}"
+            ))
+        })?;
+        Ok(Self::new(version))
+    }
+}
+
+impl Default for MonoRuntime {
+    fn default() -> Self {
+        Self::new(DEFAULT_MONO_VERSION)
+    }
+}
@@ -0,0 +1,398 @@
+use std::any::type_name;
+
+use crate::deserialize::{self, Deserialize, StructLayout, Version};
+use crate::memory::testing::TestMemory;
+use crate::memory::{Address, Architecture, X86_64};
+
+use super::class::{MonoPropertyBagItem, NestedClassesProperty};
+use super::ghashtable::RawGHashTable;
+use super::{
+    Class, ClassInternals, GHashTable, Hash as _, Image, MonoClassField,
+    MonoClassRuntimeInfo, MonoInternalHashTable, MonoTableInfo, MonoType,
+    MonoVTable, DEFAULT_MONO_VERSION,
+};
+
+/// Asserts that each field named in `offsets` is at the given offset
+/// according to `offset_of`, and that `T` occupies `num_bytes` bytes, when the
+/// target has version `version` and architecture `A`.
+pub(crate) fn assert_versioned_layout<
+    T: deserialize::VersionedDeserialize,
+    A: Architecture,
+>(
+    version: Version,
+    offset_of: fn(Version, &str) -> Option<usize>,
+    offsets: &[(&str, usize)],
+    num_bytes: usize,
+) {
+    let name = type_name::<T>();
+    for &(field, offset) in offsets {
+        assert_eq!(
+            offset_of(version, field),
+            Some(offset),
+            "offset of {name}.{field} in {version}"
+        );
+    }
+    assert_eq!(
+        T::num_bytes::<A>(version),
+        num_bytes,
+        "size of {name} in {version}"
+    );
+}
+
+/// Gets the offset of the field named `field` of `T` on X86_64.
+pub(crate) fn offset_of<T: StructLayout>(field: &str) -> usize {
+    T::field_layout::<X86_64>(field).unwrap().offset
+}
+
+/// Gets the offset of the field named `field` of [`ClassInternals`] on X86_64
+/// in [`DEFAULT_MONO_VERSION`].
+pub(crate) fn class_offset(field: &str) -> usize {
+    class_offset_on::<X86_64>(field)
+}
+
+/// Gets the offset of the field named `field` of [`Image`] on X86_64 in
+/// [`DEFAULT_MONO_VERSION`].
+pub(crate) fn image_offset(field: &str) -> usize {
+    image_offset_on::<X86_64>(field)
+}
+
+// Gets the offset of the field named `field` of `ClassInternals` on `A` in
+// `DEFAULT_MONO_VERSION`.
+fn class_offset_on<A: Architecture>(field: &str) -> usize {
+    ClassInternals::offset_of::<A>(DEFAULT_MONO_VERSION, field).unwrap()
+}
+
+// Gets the offset of the field named `field` of `Image` on `A` in
+// `DEFAULT_MONO_VERSION`.
+fn image_offset_on<A: Architecture>(field: &str) -> usize {
+    Image::offset_of::<A>(DEFAULT_MONO_VERSION, field).unwrap()
+}
+
+/// Writes a class definition named `name` in the namespace `namespace` with
+/// no fields, flags or runtime information to `memory`, returning its
+/// address.
+pub(crate) fn write_class<A: Architecture>(
+    memory: &mut TestMemory<A>,
+    namespace: &str,
+    name: &str,
+) -> Address {
+    let offset = class_offset_on::<A>;
+    let name = memory.alloc_c_string(name);
+    let namespace = memory.alloc_c_string(namespace);
+    let num_bytes =
+        <ClassInternals as deserialize::VersionedDeserialize>::num_bytes::<A>(
+            DEFAULT_MONO_VERSION,
+        );
+    let address = memory.alloc(num_bytes);
+    // Classes of kind 1 are definitions (MONO_CLASS_DEF in Mono).
+    memory.write_bytes(address + offset("class_kind"), &[1]);
+    memory.write_ptr(address + offset("name"), name);
+    memory.write_ptr(address + offset("name_space"), namespace);
+    address
+}
+
+/// Writes a vtable of the class at `class` laid out according to `version`,
+/// followed by the method slots `slots`, to `memory`, returning its address.
+pub(crate) fn write_vtable(
+    memory: &mut TestMemory,
+    class: Address,
+    version: Version,
+    slots: &[Address],
+) -> Address {
+    let offset =
+        |field| MonoVTable::offset_of::<X86_64>(version, field).unwrap();
+    let address =
+        memory.alloc(offset("vtable") + slots.len() * X86_64::POINTER_WIDTH);
+    memory.write_ptr(address + offset("class"), class);
+    for (i, slot) in slots.iter().enumerate() {
+        memory.write_ptr(
+            address + offset("vtable") + i * X86_64::POINTER_WIDTH,
+            *slot,
+        );
+    }
+    address
+}
+
+/// Gives the class at `class` the fields `fields`, each given as its name, the
+/// kind and attributes of its type, and its offset.
+pub(crate) fn write_fields(
+    memory: &mut TestMemory,
+    class: Address,
+    fields: &[(&str, u8, i16, i32)],
+) {
+    let offset = offset_of::<MonoClassField>;
+    // Fields are stored as an array, so each one is padded to its alignment.
+    let field_size = Address::new(MonoClassField::num_bytes::<X86_64>())
+        .align_forward(MonoClassField::alignment::<X86_64>())
+        .raw();
+    let address = memory.alloc(fields.len() * field_size);
+    for (i, &(name, typ, attrs, field_offset)) in fields.iter().enumerate() {
+        let field = address + i * field_size;
+        let type_address = memory.alloc(MonoType::num_bytes::<X86_64>());
+        memory.write_bytes(
+            type_address + offset_of::<MonoType>("attrs"),
+            &attrs.to_le_bytes(),
+        );
+        memory.write_bytes(type_address + offset_of::<MonoType>("typ"), &[typ]);
+        let name = memory.alloc_c_string(name);
+        memory.write_ptr(field + offset("typ"), type_address);
+        memory.write_ptr(field + offset("name"), name);
+        memory.write_ptr(field + offset("parent"), class);
+        memory
+            .write_bytes(field + offset("offset"), &field_offset.to_le_bytes());
+    }
+    memory.write_ptr(class + class_offset("fields"), address);
+    memory.write_bytes(
+        class + class_offset("field_count"),
+        &u32::try_from(fields.len()).unwrap().to_le_bytes(),
+    );
+}
+
+/// Marks the classes at `nested` as nested in the class at `class`, and gives
+/// it a list of them in its infrequent data like Mono does once it has set up
+/// the nested classes. The list is preceded by an unrelated property, since
+/// Mono keeps properties sorted by tag.
+pub(crate) fn write_nested_classes(
+    memory: &mut TestMemory,
+    class: Address,
+    nested: &[Address],
+) {
+    // GList nodes hold the data, the next node and the previous node.
+    let nodes: Vec<_> = nested.iter().map(|_| memory.alloc(24)).collect();
+    for (i, (&node, &nested_class)) in nodes.iter().zip(nested).enumerate() {
+        memory.write_ptr(node, nested_class);
+        if let Some(&next) = nodes.get(i + 1) {
+            memory.write_ptr(node + 8, next);
+        }
+        if let Some(previous) = i.checked_sub(1) {
+            memory.write_ptr(node + 16, nodes[previous]);
+        }
+        memory.write_ptr(nested_class + class_offset("nested_in"), class);
+    }
+
+    let item_offset = offset_of::<MonoPropertyBagItem>;
+    let property = memory.alloc(NestedClassesProperty::num_bytes::<X86_64>());
+    // PROP_NESTED_CLASSES
+    memory.write_bytes(property + item_offset("tag"), &4_i32.to_le_bytes());
+    if let Some(&head) = nodes.first() {
+        memory.write_ptr(
+            property + offset_of::<NestedClassesProperty>("nested_classes"),
+            head,
+        );
+    }
+    let marshal_info = memory.alloc(2 * X86_64::POINTER_WIDTH);
+    // PROP_MARSHAL_INFO
+    memory.write_bytes(marshal_info + item_offset("tag"), &1_i32.to_le_bytes());
+    memory.write_ptr(marshal_info + item_offset("next"), property);
+
+    memory.write_ptr(class + class_offset("infrequent_data"), marshal_info);
+    // nested_classes_inited
+    memory.write_bytes(class + class_offset("bitfields_3"), &[0b1000_0000]);
+}
+
+/// Gives the class at `class` runtime information listing `vtables` as its
+/// vtables in the domains with IDs 0, 1 and so on.
+pub(crate) fn write_runtime_info(
+    memory: &mut TestMemory,
+    class: Address,
+    vtables: &[Address],
+) {
+    let offset = offset_of::<MonoClassRuntimeInfo>;
+    let address = memory.alloc(
+        MonoClassRuntimeInfo::num_bytes::<X86_64>()
+            + vtables.len() * X86_64::POINTER_WIDTH,
+    );
+    let max_domain = u16::try_from(vtables.len()).unwrap() - 1;
+    memory
+        .write_bytes(address + offset("max_domain"), &max_domain.to_le_bytes());
+    for (i, vtable) in vtables.iter().enumerate() {
+        memory.write_ptr(
+            address + offset("domain_vtables") + i * X86_64::POINTER_WIDTH,
+            *vtable,
+        );
+    }
+    memory.write_ptr(class + class_offset("runtime_info"), address);
+}
+
+/// Writes an image named `name` with an empty class cache and no metadata to
+/// `memory`, returning its address.
+pub(crate) fn write_image<A: Architecture>(
+    memory: &mut TestMemory<A>,
+    name: &str,
+) -> Address {
+    let cache_offset = |field| {
+        MonoInternalHashTable::<Class>::field_layout::<A>(field)
+            .unwrap()
+            .offset
+    };
+    let name = memory.alloc_c_string(name);
+    let num_bytes = <Image as deserialize::VersionedDeserialize>::num_bytes::<A>(
+        DEFAULT_MONO_VERSION,
+    );
+    let address = memory.alloc(num_bytes);
+    memory.write_ptr(address + image_offset_on::<A>("name"), name);
+
+    let class_cache = address + image_offset_on::<A>("class_cache");
+    let slots = memory.alloc(A::POINTER_WIDTH);
+    memory
+        .write_bytes(class_cache + cache_offset("size"), &1_i32.to_le_bytes());
+    memory.write_ptr(class_cache + cache_offset("table"), slots);
+    address
+}
+
+/// Adds the class at `class` to the class cache of the image at `image` with
+/// type token `token`, and makes it point back to the image.
+pub(crate) fn cache_class<A: Architecture>(
+    memory: &mut TestMemory<A>,
+    image: Address,
+    class: Address,
+    token: u32,
+) {
+    let offset = |field| {
+        MonoInternalHashTable::<Class>::field_layout::<A>(field)
+            .unwrap()
+            .offset
+    };
+    let class_offset = class_offset_on::<A>;
+    let class_cache = image + image_offset_on::<A>("class_cache");
+    // The cache written by `write_image` has a single slot, which chains
+    // every class.
+    let slot =
+        Address::deserialize(memory, class_cache + offset("table")).unwrap();
+    let head = Option::<Address>::deserialize(memory, slot).unwrap();
+    let num_entries =
+        i32::deserialize(memory, class_cache + offset("num_entries")).unwrap();
+
+    memory.write_ptr(class + class_offset("image"), image);
+    memory
+        .write_bytes(class + class_offset("type_token"), &token.to_le_bytes());
+    if let Some(head) = head {
+        memory.write_ptr(class + class_offset("next_class_cache"), head);
+    }
+    memory.write_ptr(slot, class);
+    memory.write_bytes(
+        class_cache + offset("num_entries"),
+        &(num_entries + 1).to_le_bytes(),
+    );
+}
+
+/// Gives the image at `image` a name cache that maps each namespace in
+/// `namespaces` to its classes, given as their names and their indices in
+/// the TypeDef table.
+pub(crate) fn write_name_cache<A: Architecture>(
+    memory: &mut TestMemory<A>,
+    image: Address,
+    namespaces: &[(&str, &[(&str, u64)])],
+) {
+    let namespace_caches: Vec<_> = namespaces
+        .iter()
+        .map(|&(namespace, classes)| {
+            let cache = write_ghash_table(memory, 2, classes);
+            (namespace, cache.raw() as u64)
+        })
+        .collect();
+    let name_cache = write_ghash_table(memory, 2, &namespace_caches);
+    memory.write_ptr(image + image_offset_on::<A>("name_cache"), name_cache);
+}
+
+/// Writes a GHashTable with `size` slots holding `pairs` to `memory`,
+/// inserting each at the head of its slot like eglib does, and returns its
+/// address. Values are stored as pointer-sized integers.
+pub(crate) fn write_ghash_table<A: Architecture>(
+    memory: &mut TestMemory<A>,
+    size: i32,
+    pairs: &[(&str, u64)],
+) -> Address {
+    let width = A::POINTER_WIDTH;
+    let slots = memory.alloc(usize::try_from(size).unwrap() * width);
+    let mut heads = vec![None; size.try_into().unwrap()];
+    for &(key, value) in pairs {
+        let key_addr = memory.alloc_c_string(key);
+        // Slots hold the key, the value and the next slot in the chain.
+        let node = memory.alloc(3 * width);
+        let bucket = key.hash() as usize % heads.len();
+        memory.write_ptr(node, key_addr);
+        memory.write_bytes(node + width, &value.to_le_bytes()[..width]);
+        if let Some(head) = heads[bucket] {
+            memory.write_ptr(node + 2 * width, head);
+        }
+        memory.write_ptr(slots + bucket * width, node);
+        heads[bucket] = Some(node);
+    }
+
+    let offset = |field| {
+        RawGHashTable::<String, usize>::field_layout::<A>(field)
+            .unwrap()
+            .offset
+    };
+    let address = memory.alloc(GHashTable::<String, usize>::num_bytes::<A>());
+    memory.write_ptr(address + offset("table"), slots);
+    memory.write_bytes(address + offset("table") + width, &size.to_le_bytes());
+    memory.write_bytes(
+        address + offset("in_use"),
+        &i32::try_from(pairs.len()).unwrap().to_le_bytes(),
+    );
+    address
+}
+
+/// Writes `rows` as the metadata table with index `table` of the image at
+/// `image`. Column `i` of each row is stored in `column_sizes[i]` bytes.
+pub(crate) fn write_table(
+    memory: &mut TestMemory,
+    image: Address,
+    table: usize,
+    column_sizes: &[usize],
+    rows: &[&[u32]],
+) {
+    let row_size: usize = column_sizes.iter().sum();
+    let base = memory.alloc(row_size * rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let mut cell = base + i * row_size;
+        for (value, &size) in row.iter().zip(column_sizes) {
+            memory.write_bytes(cell, &value.to_le_bytes()[..size]);
+            cell = cell + size;
+        }
+    }
+
+    let size_bitfield = column_sizes
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, size)| bits | ((*size as u32 - 1) << (i * 2)));
+    let rows_fields = rows.len() as u32 | ((row_size as u32) << 24);
+    let info = address_of_table(image, table);
+    memory.write_ptr(info + offset_of::<MonoTableInfo>("base"), base);
+    memory.write_bytes(
+        info + offset_of::<MonoTableInfo>("rows_fields"),
+        &rows_fields.to_le_bytes(),
+    );
+    memory.write_bytes(
+        info + offset_of::<MonoTableInfo>("size_bitfield"),
+        &size_bitfield.to_le_bytes(),
+    );
+}
+
+/// Writes `bytes` as the metadata heap described by the field named `heap`
+/// (e.g. `heap_strings`) of the image at `image`.
+pub(crate) fn write_heap(
+    memory: &mut TestMemory,
+    image: Address,
+    heap: &str,
+    bytes: &[u8],
+) {
+    let data = memory.alloc(bytes.len());
+    memory.write_bytes(data, bytes);
+    let header = image + image_offset(heap);
+    memory.write_ptr(header, data);
+    memory.write_bytes(
+        header + X86_64::POINTER_WIDTH,
+        &(bytes.len() as u32).to_le_bytes(),
+    );
+}
+
+// Gets the address of the description of the metadata table with index
+// `table` of the image at `image`.
+fn address_of_table(image: Address, table: usize) -> Address {
+    image
+        + image_offset("tables")
+        + table * MonoTableInfo::num_bytes::<X86_64>()
+}
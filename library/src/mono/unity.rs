@@ -0,0 +1,54 @@
+// See UnityEngine.CoreModule in Unity
+
+use crate::deserialize::{Deserialize, PrettyPrint};
+
+use super::MonoValueType;
+
+const UNITY_ENGINE_NAMESPACE: &str = "UnityEngine";
+
+#[derive(Clone, Copy, Deserialize, PartialEq, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Vector2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Vector3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+macro_rules! unity_value_type_impl {
+    ($T:ident) => {
+        impl MonoValueType for $T {
+            const NAMESPACE: &'static str = UNITY_ENGINE_NAMESPACE;
+            const NAME: &'static str = stringify!($T);
+        }
+    };
+}
+
+unity_value_type_impl!(Vector2);
+unity_value_type_impl!(Vector3);
+unity_value_type_impl!(Quaternion);
+unity_value_type_impl!(Color);
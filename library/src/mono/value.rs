@@ -0,0 +1,334 @@
+use std::mem::size_of;
+
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{Deserialize, RemoteRef};
+use crate::memory::{
+    Address, Architecture, MemoryReader, VariableLengthAddressRange,
+};
+
+use super::{
+    Class, MonoRuntime, MonoType, MonoTypeData, MonoTypeKind, Object,
+    ObjectInternals,
+};
+
+const MAX_STRING_LENGTH: usize = 1024 * 1024;
+const MAX_ARRAY_LENGTH: usize = 1024 * 1024;
+
+// The elements of arrays are aligned to 8 bytes (see mono_64bitaligned_t in
+// mono/metadata/object-internals.h in Mono).
+const ARRAY_VECTOR_ALIGNMENT: usize = 8;
+
+/// A value read from Mono memory whose Rust type is determined by the Mono
+/// type it is read as.
+#[derive(Clone, Debug)]
+pub enum MonoValue {
+    /// A null reference.
+    Null,
+
+    Bool(bool),
+
+    /// A signed integer, or an unsigned integer of at most 32 bits.
+    Int(i64),
+
+    /// An unsigned integer of more than 32 bits.
+    UInt(u64),
+
+    Float(f64),
+
+    String(String),
+
+    /// A reference to an object that is not a string or a one-dimensional
+    /// array.
+    Object(RemoteRef<Object>),
+
+    /// The elements of a one-dimensional array.
+    Array(Vec<MonoValue>),
+
+    /// A value type, with its fields in order of offset.
+    Struct {
+        class_name: String,
+        fields: Vec<(String, MonoValue)>,
+    },
+
+    /// A value of a kind that cannot be read.
+    Unsupported(MonoTypeKind),
+}
+
+impl MonoValue {
+    /// Reads the value of type `typ` stored at `address` in a target whose
+    /// runtime is described by `runtime`. Values of value types are read as
+    /// if they were stored inline (i.e. without an object header).
+    ///
+    /// Returns an [`Error`](DeserializeError) if the value cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        typ: &MonoType,
+        address: Address,
+        runtime: &MonoRuntime,
+    ) -> Result<Self, DeserializeError> {
+        let kind = typ.kind()?;
+        Ok(match kind {
+            MonoTypeKind::Boolean => {
+                Self::Bool(bool::deserialize(reader, address)?)
+            }
+            MonoTypeKind::I1 => {
+                Self::Int(i8::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::U1 => {
+                Self::Int(u8::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::I2 => {
+                Self::Int(i16::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::U2 | MonoTypeKind::Char => {
+                Self::Int(u16::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::I4 => {
+                Self::Int(i32::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::U4 => {
+                Self::Int(u32::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::I8 => Self::Int(i64::deserialize(reader, address)?),
+            MonoTypeKind::U8 => Self::UInt(u64::deserialize(reader, address)?),
+            MonoTypeKind::I => {
+                Self::Int(isize::deserialize(reader, address)?.try_into()?)
+            }
+            MonoTypeKind::U => {
+                Self::UInt(usize::deserialize(reader, address)?.try_into()?)
+            }
+            MonoTypeKind::R4 => {
+                Self::Float(f32::deserialize(reader, address)?.into())
+            }
+            MonoTypeKind::R8 => Self::Float(f64::deserialize(reader, address)?),
+            MonoTypeKind::String => {
+                match Option::<Address>::deserialize(reader, address)? {
+                    Some(string_addr) => {
+                        Self::String(read_mono_string(reader, string_addr)?)
+                    }
+                    None => Self::Null,
+                }
+            }
+            MonoTypeKind::SzArray => {
+                let MonoTypeData::ElementClass(element_class) =
+                    typ.resolve_data()?
+                else {
+                    return Ok(Self::Unsupported(kind));
+                };
+                match Option::<Address>::deserialize(reader, address)? {
+                    Some(array_addr) => {
+                        let element_class = Class::read(
+                            reader,
+                            element_class.address(),
+                            runtime,
+                        )?;
+                        Self::Array(read_array(
+                            reader,
+                            &element_class,
+                            array_addr,
+                        )?)
+                    }
+                    None => Self::Null,
+                }
+            }
+            MonoTypeKind::Class
+            | MonoTypeKind::Object
+            | MonoTypeKind::Array => read_reference(reader, address)?,
+            MonoTypeKind::ValueType => {
+                let MonoTypeData::Class(class) = typ.resolve_data()? else {
+                    return Ok(Self::Unsupported(kind));
+                };
+                let class = Class::read(reader, class.address(), runtime)?;
+                read_struct(reader, &class, address)?
+            }
+            MonoTypeKind::GenericInst => {
+                let MonoTypeData::GenericClass(generic_class) =
+                    typ.resolve_data()?
+                else {
+                    return Ok(Self::Unsupported(kind));
+                };
+                let generic_class = generic_class.read(reader)?;
+                // The instantiated class has the actual layout of the value,
+                // but may not have been created yet.
+                let class_ptr = generic_class
+                    .cached_class
+                    .unwrap_or(generic_class.container_class);
+                let class = Class::read(reader, class_ptr.address(), runtime)?;
+                if class.is_value_type() {
+                    read_struct(reader, &class, address)?
+                } else {
+                    read_reference(reader, address)?
+                }
+            }
+            _ => Self::Unsupported(kind),
+        })
+    }
+}
+
+// Reads a reference to an object stored at `address`.
+fn read_reference<M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+) -> Result<MonoValue, DeserializeError> {
+    Ok(match Option::<Address>::deserialize(reader, address)? {
+        Some(object_addr) => MonoValue::Object(RemoteRef::new(object_addr)),
+        None => MonoValue::Null,
+    })
+}
+
+// Reads the MonoString object at `address` (see MonoString in
+// mono/metadata/object-internals.h in Mono).
+fn read_mono_string<M: MemoryReader>(
+    reader: &mut M,
+    address: Address,
+) -> Result<String, DeserializeError> {
+    let length_addr = address + ObjectInternals::num_bytes::<M::Architecture>();
+    let length: usize = i32::deserialize(reader, length_addr)?.try_into()?;
+    if length > MAX_STRING_LENGTH {
+        return Err(DeserializeError::InvalidStateError(format!(
+            "String at {address} has length [{length}], which is more than \
+             {MAX_STRING_LENGTH}"
+        )));
+    }
+
+    let bytes = reader.read_vec(VariableLengthAddressRange {
+        start: length_addr + size_of::<i32>(),
+        num_bytes: length * size_of::<u16>(),
+    })?;
+    let chars = bytes
+        .chunks_exact(size_of::<u16>())
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect::<Vec<_>>();
+    String::from_utf16(&chars).map_err(|_| {
+        DeserializeError::InvalidStateError(format!(
+            "String at {address} is not valid UTF-16"
+        ))
+    })
+}
+
+// Reads the elements of the MonoArray at `address`, whose elements are
+// instances of `element_class` (see MonoArray in
+// mono/metadata/object-internals.h in Mono).
+fn read_array<M: MemoryReader>(
+    reader: &mut M,
+    element_class: &Class,
+    address: Address,
+) -> Result<Vec<MonoValue>, DeserializeError> {
+    let pointer_width = M::Architecture::POINTER_WIDTH;
+    // The object header is followed by the bounds pointer and the length.
+    let length_addr = address
+        + ObjectInternals::num_bytes::<M::Architecture>()
+        + pointer_width;
+    let length = usize::deserialize(reader, length_addr)?;
+    if length > MAX_ARRAY_LENGTH {
+        return Err(DeserializeError::InvalidStateError(format!(
+            "Array at {address} has length [{length}], which is more than \
+             {MAX_ARRAY_LENGTH}"
+        )));
+    }
+
+    let element_type = &element_class.internals.byval_arg;
+    let element_size = element_class.element_size::<M::Architecture>()?;
+    let vector_addr =
+        (length_addr + pointer_width).align_forward(ARRAY_VECTOR_ALIGNMENT);
+    (0..length)
+        .map(|i| {
+            MonoValue::read(
+                reader,
+                element_type,
+                vector_addr + i * element_size,
+                element_class.runtime(),
+            )
+        })
+        .collect()
+}
+
+// Reads the instance fields of the value type `class` stored inline at
+// `address`. Enums are read as their underlying value.
+fn read_struct<M: MemoryReader>(
+    reader: &mut M,
+    class: &Class,
+    address: Address,
+) -> Result<MonoValue, DeserializeError> {
+    let mut fields = class
+        .fields()
+        .filter(|resolved| !resolved.field.typ.value.is_static())
+        .map(|resolved| &resolved.field)
+        .collect::<Vec<_>>();
+    fields.sort_by_key(|field| field.offset);
+
+    let mut values = Vec::with_capacity(fields.len());
+    for field in fields {
+        let field_addr =
+            address + class.inline_field_offset::<M::Architecture>(field)?;
+        let value = MonoValue::read(
+            reader,
+            &field.typ.value,
+            field_addr,
+            class.runtime(),
+        )?;
+        values.push((field.name.clone(), value));
+    }
+
+    if class.is_enum() {
+        if let Some((_, value)) = values.pop() {
+            return Ok(value);
+        }
+    }
+    Ok(MonoValue::Struct {
+        class_name: class.internals.name.clone(),
+        fields: values,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::X86_64;
+
+    use super::*;
+
+    // Writes a MonoString holding `length` followed by `chars` and returns its
+    // address.
+    fn write_string(
+        memory: &mut TestMemory,
+        length: i32,
+        chars: &[u16],
+    ) -> Address {
+        let header = ObjectInternals::num_bytes::<X86_64>();
+        let address = memory.alloc(header + size_of::<i32>() + chars.len() * 2);
+        memory.write_bytes(address + header, &length.to_le_bytes());
+        let bytes = chars
+            .iter()
+            .flat_map(|c| c.to_le_bytes())
+            .collect::<Vec<_>>();
+        memory.write_bytes(address + header + size_of::<i32>(), &bytes);
+        address
+    }
+
+    #[test]
+    fn reads_string_characters_in_one_read() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let chars = "Grüße 🎮".encode_utf16().collect::<Vec<_>>();
+        let length = i32::try_from(chars.len()).unwrap();
+        let address = write_string(&mut memory, length, &chars);
+
+        let num_reads = memory.num_reads();
+        let string = read_mono_string(&mut memory, address).unwrap();
+        assert_eq!(string, "Grüße 🎮");
+        // One read for the length and one for the characters.
+        assert_eq!(memory.num_reads() - num_reads, 2);
+    }
+
+    #[test]
+    fn rejects_invalid_strings() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let too_long = i32::try_from(MAX_STRING_LENGTH + 1).unwrap();
+        let address = write_string(&mut memory, too_long, &[]);
+        assert!(read_mono_string(&mut memory, address).is_err());
+        assert_eq!(memory.num_reads(), 1);
+
+        let unpaired_surrogate = write_string(&mut memory, 1, &[0xd800]);
+        assert!(read_mono_string(&mut memory, unpaired_surrogate).is_err());
+    }
+}
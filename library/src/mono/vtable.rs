@@ -0,0 +1,224 @@
+// See mono/metadata/object-internals.h and mono/metadata/class-internals.h in
+// Mono
+
+use crate::deserialize::Error as DeserializeError;
+use crate::deserialize::{
+    Bitfield, Deserialize, PrettyPrint, Ptr, Version, VersionedDeserialize,
+    ZeroLengthArray,
+};
+use crate::memory::{Address, MemoryReader};
+
+use super::{Class, MonoDomain};
+
+// Mono 6 added the `flags` and `interp_vtable` fields. Unity's fork of Mono
+// reports version 5.11 and has neither.
+#[derive(Bitfield, PrettyPrint, VersionedDeserialize)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct MonoVTable {
+    pub class: Ptr<Class>,
+    pub gc_descr: Option<Address>,
+    pub domain: Option<Ptr<MonoDomain>>,
+    pub typ: Option<Address>,
+    pub interface_bitmap: Option<Address>,
+    pub max_interface_id: u32,
+    pub rank: u8,
+    pub initialized: u8,
+    #[versioned(since = "6.0")]
+    pub flags: Option<u8>,
+    #[bitfield(
+        pub remote: bool = 0,
+        pub init_failed: bool = 1,
+        pub has_static_fields: bool = 2,
+    )]
+    bitfield: u8,
+    pub imt_collisions_bitmap: u32,
+    pub runtime_generic_context: Option<Address>,
+    #[versioned(since = "6.0")]
+    pub interp_vtable: Option<Option<Address>>,
+    pub vtable: ZeroLengthArray<Address>,
+}
+
+impl MonoVTable {
+    /// Reads the vtable at `address`, laid out according to the runtime
+    /// version `version`.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the vtable cannot be read.
+    pub fn read<M: MemoryReader>(
+        reader: &mut M,
+        address: Address,
+        version: Version,
+    ) -> Result<Self, DeserializeError> {
+        Self::deserialize_versioned(reader, address, version)
+    }
+}
+
+#[derive(Deserialize, PrettyPrint)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[deserialize(remote)]
+pub struct MonoClassRuntimeInfo {
+    pub max_domain: u16,
+    pub domain_vtables: ZeroLengthArray<Option<Address>>,
+}
+
+impl MonoClassRuntimeInfo {
+    /// Reads the vtable of the class in the domain with ID `domain_id`,
+    /// laid out according to the runtime version `version`. Returns `None`
+    /// if the class has not been initialized in that domain.
+    ///
+    /// Returns an [`Error`](DeserializeError) if the vtable cannot be read.
+    pub fn vtable<M: MemoryReader>(
+        &self,
+        reader: &mut M,
+        domain_id: usize,
+        version: Version,
+    ) -> Result<Option<MonoVTable>, DeserializeError> {
+        if domain_id > usize::from(self.max_domain) {
+            return Ok(None);
+        }
+        self.domain_vtables
+            .nth_element(reader, domain_id)?
+            .map(|vtable| MonoVTable::read(reader, vtable, version))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::memory::testing::TestMemory;
+    use crate::memory::{Architecture, X86, X86_64};
+    use crate::mono::testing::write_vtable;
+    use crate::mono::DEFAULT_MONO_VERSION;
+
+    use super::*;
+
+    const MONO_6_12: Version = Version::new(6, 12, 0, 0);
+
+    #[test]
+    fn decodes_flags_from_low_bits() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let class = Address::new(0x2000);
+        let address =
+            write_vtable(&mut memory, class, DEFAULT_MONO_VERSION, &[]);
+        let bitfield =
+            MonoVTable::offset_of::<X86_64>(DEFAULT_MONO_VERSION, "bitfield");
+        memory.write_bytes(address + bitfield.unwrap(), &[0b101]);
+
+        let vtable =
+            MonoVTable::read(&mut memory, address, DEFAULT_MONO_VERSION)
+                .unwrap();
+        assert_eq!(vtable.class.address(), class);
+        assert!(vtable.remote());
+        assert!(!vtable.init_failed());
+        assert!(vtable.has_static_fields());
+        assert_eq!(vtable.flags, None);
+        assert_eq!(vtable.interp_vtable, None);
+    }
+
+    // Asserts that each field named in `offsets` is at the given offset, or
+    // is absent if the offset is `None`, when the target has version
+    // `version` and architecture `A`, and that the vtable occupies
+    // `num_bytes` bytes before its method slots.
+    fn assert_layout<A: Architecture>(
+        version: Version,
+        offsets: &[(&str, Option<usize>)],
+        num_bytes: usize,
+    ) {
+        for &(field, offset) in offsets {
+            assert_eq!(
+                MonoVTable::offset_of::<A>(version, field),
+                offset,
+                "offset of MonoVTable.{field} in {version}"
+            );
+        }
+        assert_eq!(MonoVTable::num_bytes::<A>(version), num_bytes);
+    }
+
+    #[test]
+    fn layout_matches_mono_x86_64() {
+        let common = [
+            ("class", Some(0)),
+            ("gc_descr", Some(8)),
+            ("domain", Some(16)),
+            ("typ", Some(24)),
+            ("interface_bitmap", Some(32)),
+            ("max_interface_id", Some(40)),
+            ("rank", Some(44)),
+            ("initialized", Some(45)),
+            ("imt_collisions_bitmap", Some(48)),
+            ("runtime_generic_context", Some(56)),
+        ];
+        assert_layout::<X86_64>(DEFAULT_MONO_VERSION, &common, 64);
+        assert_layout::<X86_64>(
+            DEFAULT_MONO_VERSION,
+            &[
+                ("flags", None),
+                ("bitfield", Some(46)),
+                ("interp_vtable", None),
+                ("vtable", Some(64)),
+            ],
+            64,
+        );
+        assert_layout::<X86_64>(MONO_6_12, &common, 72);
+        assert_layout::<X86_64>(
+            MONO_6_12,
+            &[
+                ("flags", Some(46)),
+                ("bitfield", Some(47)),
+                ("interp_vtable", Some(64)),
+                ("vtable", Some(72)),
+            ],
+            72,
+        );
+    }
+
+    #[test]
+    fn layout_matches_mono_x86() {
+        let common = [
+            ("class", Some(0)),
+            ("gc_descr", Some(4)),
+            ("domain", Some(8)),
+            ("typ", Some(12)),
+            ("interface_bitmap", Some(16)),
+            ("max_interface_id", Some(20)),
+            ("rank", Some(24)),
+            ("initialized", Some(25)),
+            ("imt_collisions_bitmap", Some(28)),
+            ("runtime_generic_context", Some(32)),
+        ];
+        assert_layout::<X86>(DEFAULT_MONO_VERSION, &common, 36);
+        assert_layout::<X86>(
+            DEFAULT_MONO_VERSION,
+            &[
+                ("flags", None),
+                ("bitfield", Some(26)),
+                ("interp_vtable", None),
+                ("vtable", Some(36)),
+            ],
+            36,
+        );
+        assert_layout::<X86>(MONO_6_12, &common, 40);
+        assert_layout::<X86>(
+            MONO_6_12,
+            &[
+                ("flags", Some(26)),
+                ("bitfield", Some(27)),
+                ("interp_vtable", Some(36)),
+                ("vtable", Some(40)),
+            ],
+            40,
+        );
+    }
+
+    #[test]
+    fn reads_slots_after_versioned_fields() {
+        let mut memory = TestMemory::<X86_64>::new(Address::new(0x1000));
+        let slot = Address::new(0x3000);
+        let address =
+            write_vtable(&mut memory, Address::new(0x2000), MONO_6_12, &[slot]);
+
+        let vtable = MonoVTable::read(&mut memory, address, MONO_6_12).unwrap();
+        assert_eq!(vtable.flags, Some(0));
+        assert_eq!(vtable.interp_vtable, Some(None));
+        assert_eq!(vtable.vtable.nth_element(&mut memory, 0).unwrap(), slot);
+    }
+}
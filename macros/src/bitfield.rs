@@ -0,0 +1,170 @@
+use proc_macro2::TokenStream;
+
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DeriveInput, Ident, Index, LitInt, Token, Type, Visibility,
+};
+
+const ATTRIBUTE_NAME: &str = "bitfield";
+
+// A single named bit range, e.g. `pub rows: u32 = 0..24` or
+// `remote: bool = 0`.
+struct BitRange {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    name: Ident,
+    ty: Type,
+    start: LitInt,
+    end: Option<LitInt>,
+}
+
+impl Parse for BitRange {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let vis = input.parse()?;
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let start = input.parse()?;
+        let end = if input.peek(Token![..]) {
+            input.parse::<Token![..]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            attrs,
+            vis,
+            name,
+            ty,
+            start,
+            end,
+        })
+    }
+}
+
+impl BitRange {
+    // Returns the (inclusive) first bit and (exclusive) last bit of the range.
+    fn bounds(&self) -> syn::Result<(u32, u32)> {
+        let start: u32 = self.start.base10_parse()?;
+        let end: u32 = match self.end {
+            None => start + 1,
+            Some(ref end) => end.base10_parse()?,
+        };
+        if end <= start {
+            return Err(syn::Error::new(
+                self.start.span(),
+                "bit range must contain at least one bit",
+            ));
+        }
+        Ok((start, end))
+    }
+
+    fn is_bool(&self) -> bool {
+        matches!(self.ty, Type::Path(ref path) if path.path.is_ident("bool"))
+    }
+}
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    match try_expand(input) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn try_expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+
+    let Data::Struct(ref struct_data) = input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "Bitfield can only be derived on structs",
+        ));
+    };
+
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let mut getters = Vec::<TokenStream>::new();
+    for (i, field) in struct_data.fields.iter().enumerate() {
+        let member = field.ident.as_ref().map_or_else(
+            || Index::from(i).into_token_stream(),
+            ToTokens::into_token_stream,
+        );
+
+        for attr in &field.attrs {
+            if !attr.path.is_ident(ATTRIBUTE_NAME) {
+                continue;
+            }
+            let ranges = attr.parse_args_with(
+                Punctuated::<BitRange, Token![,]>::parse_terminated,
+            )?;
+            for range in &ranges {
+                getters.push(getter(&member, &field.ty, range)?);
+            }
+        }
+    }
+
+    Ok(quote! {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #(#getters)*
+        }
+    })
+}
+
+fn getter(
+    member: &TokenStream,
+    storage_ty: &Type,
+    range: &BitRange,
+) -> syn::Result<TokenStream> {
+    let (start, end) = range.bounds()?;
+    let width = end - start;
+
+    let BitRange {
+        attrs,
+        vis,
+        name,
+        ty,
+        ..
+    } = range;
+
+    let check_bounds = quote! {
+        const {
+            assert!(
+                #end as usize <= std::mem::size_of::<#storage_ty>() * 8,
+                "bit range exceeds the size of its storage field",
+            );
+        }
+    };
+
+    let storage_bits = quote!((std::mem::size_of::<#storage_ty>() * 8) as u32);
+    let extract = quote! {
+        (self.#member >> #start)
+            & (<#storage_ty>::MAX >> (#storage_bits - #width))
+    };
+
+    let value = if range.is_bool() {
+        if width != 1 {
+            return Err(syn::Error::new(
+                range.ty.span(),
+                "bool bit ranges must contain exactly one bit",
+            ));
+        }
+        quote!(#extract != 0)
+    } else {
+        quote!((#extract) as #ty)
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #[must_use]
+        #vis fn #name(&self) -> #ty {
+            #check_bounds
+            #value
+        }
+    })
+}
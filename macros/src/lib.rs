@@ -4,9 +4,29 @@ use proc_macro2::{Span, TokenStream};
 
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Ident, Index};
+use syn::{
+    parse_macro_input, Attribute, Data, DataStruct, DeriveInput, Fields,
+    Generics, Ident, Index, Lit, Meta, NestedMeta, Visibility,
+};
 
-#[proc_macro_derive(Deserialize)]
+mod bitfield;
+mod prettyprint;
+mod versioned;
+
+/// Implements `Deserialize` and `StructLayout` for a struct by deserializing
+/// each of its fields in order.
+///
+/// The struct may be annotated with `#[deserialize(validate)]` to check the
+/// deserialized value with its `Validate` implementation, or with
+/// `#[deserialize(validate = "path::to::function")]` to check it with a
+/// function of the same signature as `Validate::validate`.
+///
+/// Structs with named fields may also be annotated with
+/// `#[deserialize(remote)]` to generate a method on `RemoteRef<Self>` for each
+/// field, with the same name and visibility as the field, that reads only that
+/// field. Since these are inherent methods of `RemoteRef`, this is only
+/// supported for structs defined in `grub_split_library`.
+#[proc_macro_derive(Deserialize, attributes(deserialize))]
 pub fn derive_deserialize(
     input: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
@@ -21,38 +41,309 @@ pub fn derive_deserialize(
     let generics = input.generics;
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
-    let num_bytes = num_bytes_const(struct_data);
-    let alignment = alignment_const(struct_data);
+    let num_bytes = num_bytes_expr(struct_data);
+    let alignment = alignment_expr(struct_data);
+    let field_layouts = field_layouts_expr(struct_data);
 
     let struct_name_str = struct_name.to_string();
     let create_struct =
         create_struct_expr(struct_name_str.as_ref(), struct_data);
+    let options = match DeserializeOptions::parse(&input.attrs) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let validate = validate_stmt(&struct_name_str, options.validator.as_ref());
+    let remote = if options.remote {
+        match remote_impl(&struct_name, &generics, struct_data) {
+            Ok(remote) => remote,
+            Err(error) => return error.to_compile_error().into(),
+        }
+    } else {
+        TokenStream::new()
+    };
 
     let expanded = quote! {
         impl #impl_generics grub_split_library::deserialize::Deserialize for #struct_name #ty_generics #where_clause {
-            const NUM_BYTES: usize = #num_bytes;
-            const ALIGNMENT: usize = #alignment;
+            fn num_bytes<A: grub_split_library::memory::Architecture>() -> usize {
+                #num_bytes
+            }
+
+            fn alignment<A: grub_split_library::memory::Architecture>() -> usize {
+                #alignment
+            }
 
             fn deserialize<M: grub_split_library::memory::MemoryReader>(
                 reader: &mut M,
                 address: grub_split_library::memory::Address,
             ) -> Result<Self, grub_split_library::deserialize::Error> {
-                Ok(#create_struct)
+                // Read the whole struct at once; fields are then deserialized
+                // from the buffer, and only pointer targets hit `reader`. If
+                // the struct cannot be read whole, each field is read on its
+                // own instead so that errors name the field that failed.
+                let range = grub_split_library::memory::VariableLengthAddressRange {
+                    start: address,
+                    num_bytes: <Self as grub_split_library::deserialize::Deserialize>::num_bytes::<
+                        <M as grub_split_library::memory::MemoryReader>::Architecture
+                    >(),
+                };
+                let mut buffered_reader = match reader.read_vec(range) {
+                    Ok(buffer) => grub_split_library::memory::buffered::BufferedMemoryReader::with_buffer(
+                        reader,
+                        address,
+                        buffer,
+                    ),
+                    Err(_) => grub_split_library::memory::buffered::BufferedMemoryReader::unbuffered(reader),
+                };
+                let reader = &mut buffered_reader;
+                let value = #create_struct;
+                #validate
+                Ok(value)
+            }
+        }
+
+        impl #impl_generics grub_split_library::deserialize::StructLayout for #struct_name #ty_generics #where_clause {
+            const NAME: &'static str = #struct_name_str;
+            fn fields<A: grub_split_library::memory::Architecture>() -> Vec<grub_split_library::deserialize::FieldLayout> {
+                #field_layouts.to_vec()
             }
         }
+
+        #remote
     };
 
     expanded.into()
 }
 
-// Generate a const expression for NUM_BYTES
-fn num_bytes_const(struct_data: &DataStruct) -> TokenStream {
+/// Generates typed getters for named bit ranges within integer fields.
+///
+/// Each storage field may be annotated with
+/// `#[bitfield(<vis> <name>: <type> = <start>[..<end>], ...)]`, where bits are
+/// numbered from the least significant bit of the storage field and `<end>`
+/// is exclusive. `bool` getters must cover exactly one bit; any other getter
+/// type receives the bits shifted down to bit 0. Storage fields must have an
+/// unsigned integer type.
+#[proc_macro_derive(Bitfield, attributes(bitfield))]
+pub fn derive_bitfield(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    bitfield::expand(input).into()
+}
+
+/// Implements `PrettyPrint` and `Debug` for a struct by printing each of its
+/// fields with `PrettyPrint`.
+///
+/// Every type parameter of the struct is required to implement `PrettyPrint`.
+#[proc_macro_derive(PrettyPrint)]
+pub fn derive_pretty_print(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    prettyprint::expand(input).into()
+}
+
+/// Implements `VersionedDeserialize` for a struct whose layout depends on the
+/// version of the target process.
+///
+/// Fields are laid out sequentially as with `Deserialize`, except that each
+/// field may be annotated with `#[versioned(...)]` containing any of:
+/// - `since = "<version>"`: the field is only present in `<version>` and
+///   later.
+/// - `until = "<version>"`: the field is only present before `<version>`.
+/// - `offset("<version>" = <offset>, ...)`: in `<version>` and later (until
+///   the next listed version), the field is at byte `<offset>` from the start
+///   of the struct instead of directly after the previous field.
+///
+/// Fields with `since` or `until` must have type `Option<T>` and are `None` in
+/// versions where they are not present.
+///
+/// Structs with named fields may also be annotated with `#[versioned(remote)]`
+/// to generate a method on `RemoteRef<Self>` for each field, as with
+/// `#[deserialize(remote)]`, that takes the version to read the field in as
+/// well.
+#[proc_macro_derive(VersionedDeserialize, attributes(versioned))]
+pub fn derive_versioned_deserialize(
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    versioned::expand(input).into()
+}
+
+// The options given in a struct's `#[deserialize(...)]` attributes.
+#[derive(Default)]
+struct DeserializeOptions {
+    // The function used to validate deserialized values, if any.
+    validator: Option<TokenStream>,
+    // Whether to generate field accessors on `RemoteRef<Self>`.
+    remote: bool,
+}
+
+impl DeserializeOptions {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        for attr in attrs {
+            if !attr.path.is_ident("deserialize") {
+                continue;
+            }
+            let Meta::List(list) = attr.parse_meta()? else {
+                return Err(syn::Error::new(
+                    attr.span(),
+                    "expected `#[deserialize(...)]`",
+                ));
+            };
+            for nested in list.nested {
+                match nested {
+                    NestedMeta::Meta(Meta::Path(path))
+                        if path.is_ident("validate") =>
+                    {
+                        options.validator = Some(quote! {
+                            <Self as grub_split_library::deserialize::Validate>::validate
+                        });
+                    }
+                    NestedMeta::Meta(Meta::NameValue(name_value))
+                        if name_value.path.is_ident("validate") =>
+                    {
+                        let Lit::Str(path) = name_value.lit else {
+                            return Err(syn::Error::new(
+                                name_value.lit.span(),
+                                "expected a string containing a function path",
+                            ));
+                        };
+                        let path: syn::Path = path.parse()?;
+                        options.validator = Some(quote!(#path));
+                    }
+                    NestedMeta::Meta(Meta::Path(path))
+                        if path.is_ident("remote") =>
+                    {
+                        options.remote = true;
+                    }
+                    other => {
+                        return Err(syn::Error::new(
+                            other.span(),
+                            "unknown deserialize option",
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(options)
+    }
+}
+
+// Generate a statement that validates `value` with `validator`, if any.
+fn validate_stmt(
+    struct_name: &str,
+    validator: Option<&TokenStream>,
+) -> TokenStream {
+    validator.map_or_else(TokenStream::new, |validator| {
+        quote! {
+            #validator(&value, address).map_err(|err| err.within(
+                grub_split_library::deserialize::PathSegment::Struct {
+                    struct_name: #struct_name,
+                    address,
+                }
+            ))?;
+        }
+    })
+}
+
+// Generate an impl of `RemoteRef<Self>` with an accessor for each field
+fn remote_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    struct_data: &DataStruct,
+) -> syn::Result<TokenStream> {
+    let Fields::Named(ref fields) = struct_data.fields else {
+        return Err(syn::Error::new(
+            struct_name.span(),
+            "remote accessors can only be generated for structs with named \
+             fields",
+        ));
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let struct_name_str = struct_name.to_string();
+    let num_fields = fields.named.len();
+    let field_layouts = field_layouts_expr(struct_data);
+
+    let accessors = fields.named.iter().enumerate().filter_map(|(i, field)| {
+        let ident = field.ident.as_ref()?;
+        let vis = &field.vis;
+        let ty = &field.ty;
+        let field_name_str = ident.to_string();
+        let doc = format!(
+            "Reads the `{field_name_str}` field of the referenced \
+             `{struct_name_str}`."
+        );
+        // Accessors for private fields may go unused.
+        let allow_dead_code = if matches!(vis, Visibility::Inherited) {
+            quote!(#[allow(dead_code)])
+        } else {
+            TokenStream::new()
+        };
+        Some(quote! {
+            #[doc = #doc]
+            #allow_dead_code
+            #vis fn #ident<M: grub_split_library::memory::MemoryReader>(
+                &self,
+                reader: &mut M,
+            ) -> Result<#ty, grub_split_library::deserialize::Error> {
+                let address = self.address() + Self::field_layouts::<
+                    <M as grub_split_library::memory::MemoryReader>::Architecture
+                >()[#i].offset;
+                <#ty as grub_split_library::deserialize::Deserialize>::deserialize(
+                    reader,
+                    address,
+                ).map_err(|err| err.within(
+                    grub_split_library::deserialize::PathSegment::Field {
+                        struct_name: #struct_name_str,
+                        field_name: #field_name_str,
+                        address,
+                    }
+                ))
+            }
+        })
+    });
+
+    Ok(quote! {
+        impl #impl_generics grub_split_library::deserialize::RemoteRef<#struct_name #ty_generics> #where_clause {
+            fn field_layouts<A: grub_split_library::memory::Architecture>()
+                -> [grub_split_library::deserialize::FieldLayout; #num_fields]
+            {
+                #field_layouts
+            }
+
+            #(#accessors)*
+        }
+    })
+}
+
+// Generate an expression for the number of bytes in a field of type `ty` on
+// architecture `arch`
+fn field_num_bytes(ty: &syn::Type, arch: &TokenStream) -> TokenStream {
+    quote_spanned! { ty.span() =>
+        <#ty as grub_split_library::deserialize::Deserialize>::num_bytes::<#arch>()
+    }
+}
+
+// Generate an expression for the alignment of a field of type `ty` on
+// architecture `arch`
+fn field_alignment(ty: &syn::Type, arch: &TokenStream) -> TokenStream {
+    quote_spanned! { ty.span() =>
+        <#ty as grub_split_library::deserialize::Deserialize>::alignment::<#arch>()
+    }
+}
+
+// Generate an expression for Deserialize::num_bytes
+fn num_bytes_expr(struct_data: &DataStruct) -> TokenStream {
+    let arch = quote!(A);
     let mut result = quote!(grub_split_library::memory::Address::new(0));
 
     for field in &struct_data.fields {
-        let ty = &field.ty;
+        let num_bytes = field_num_bytes(&field.ty, &arch);
+        let alignment = field_alignment(&field.ty, &arch);
         result = quote_spanned! { field.span() =>
-            (#result.align_forward(<#ty>::ALIGNMENT).add_const(<#ty>::NUM_BYTES))
+            (#result.align_forward(#alignment) + #num_bytes)
         };
     }
     quote! {
@@ -60,26 +351,61 @@ fn num_bytes_const(struct_data: &DataStruct) -> TokenStream {
     }
 }
 
-// Generate a const expression for ALIGNMENT
-fn alignment_const(struct_data: &DataStruct) -> TokenStream {
+// Generate an expression for Deserialize::alignment
+fn alignment_expr(struct_data: &DataStruct) -> TokenStream {
+    let arch = quote!(A);
     let mut result = quote!(1);
     for field in &struct_data.fields {
-        let ty = &field.ty;
-        let alignment = quote_spanned! { field.span() =>
-            <#ty>::ALIGNMENT
-        };
-        result = quote!(max(#result, #alignment));
+        let alignment = field_alignment(&field.ty, &arch);
+        result = quote!(std::cmp::max(#result, #alignment));
     }
-    quote! {
-        {
-            const fn max(a: usize, b: usize) -> usize {
-                if a >= b { a } else { b }
+    result
+}
+
+// Generate an expression for an array of the layouts of each field
+fn field_layouts_expr(struct_data: &DataStruct) -> TokenStream {
+    let arch = quote!(A);
+    let layouts = struct_data.fields.iter().enumerate().map(|(i, field)| {
+        let ty = &field.ty;
+        let field_name_str = field
+            .ident
+            .as_ref()
+            .map_or_else(|| i.to_string(), std::string::ToString::to_string);
+        let type_name = type_name_str(ty);
+        let num_bytes = field_num_bytes(ty, &arch);
+        let alignment = field_alignment(ty, &arch);
+        quote_spanned! { field.span() =>
+            grub_split_library::deserialize::FieldLayout {
+                name: #field_name_str,
+                offset: 0,
+                num_bytes: #num_bytes,
+                alignment: #alignment,
+                type_name: #type_name,
             }
-            #result
         }
+    });
+
+    quote! {
+        grub_split_library::deserialize::FieldLayout::sequential([
+            #(#layouts),*
+        ])
     }
 }
 
+// Render a type as it would be written in source code (e.g. `Option<Address>`
+// rather than the token-spaced `Option < Address >`).
+fn type_name_str(ty: &syn::Type) -> String {
+    quote!(#ty)
+        .to_string()
+        .replace(" <", "<")
+        .replace("< ", "<")
+        .replace(" >", ">")
+        .replace(" ,", ",")
+        .replace(" ;", ";")
+        .replace(" :: ", "::")
+        .replace("& ", "&")
+}
+
 fn create_struct_expr(
     struct_name: &str,
     struct_data: &DataStruct,
@@ -88,25 +414,29 @@ fn create_struct_expr(
         .map(|i| Ident::new(&format!("field{i}"), Span::mixed_site()))
         .collect();
 
+    let arch =
+        quote!(<M as grub_split_library::memory::MemoryReader>::Architecture);
+
     let initializers = Iterator::zip(struct_data.fields.iter().enumerate(), &identifiers)
         .map(|((i, field), ident)| {
         let ty = &field.ty;
+        let num_bytes = field_num_bytes(ty, &arch);
+        let alignment = field_alignment(ty, &arch);
         let field_name_str = field.ident.as_ref().map_or_else(
             || i.to_string(),
             std::string::ToString::to_string,
         );
         let extract_field = quote_spanned! { field.span() =>
-            next_addr = next_addr.align_forward(<#ty>::ALIGNMENT);
+            next_addr = next_addr.align_forward(#alignment);
             let #ident = grub_split_library::deserialize::Deserialize::deserialize(
                 reader,
                 next_addr).map_err(
-                    |err| grub_split_library::deserialize::Error::WithContext(
-                        Box::new(err),
-                        std::format!(
-                            "while deserializing {}.{}",
-                            #struct_name,
-                            #field_name_str,
-                        )
+                    |err| err.within(
+                        grub_split_library::deserialize::PathSegment::Field {
+                            struct_name: #struct_name,
+                            field_name: #field_name_str,
+                            address: next_addr,
+                        }
                     )
                 )?;
         };
@@ -115,7 +445,7 @@ fn create_struct_expr(
         } else {
             quote! {
                 #extract_field
-                next_addr = next_addr + <#ty>::NUM_BYTES;
+                next_addr = next_addr + #num_bytes;
             }
         }
     });
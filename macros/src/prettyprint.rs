@@ -0,0 +1,107 @@
+use proc_macro2::TokenStream;
+
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Data, DeriveInput, Fields, Index};
+
+pub fn expand(mut input: DeriveInput) -> TokenStream {
+    let struct_name = &input.ident;
+    let struct_name_str = struct_name.to_string();
+
+    let Data::Struct(ref struct_data) = input.data else {
+        return syn::Error::new(
+            input.span(),
+            "PrettyPrint can only be derived on structs",
+        )
+        .to_compile_error();
+    };
+
+    let body = match struct_data.fields {
+        Fields::Named(ref fields) => {
+            let entries = fields.named.iter().map(|field| {
+                let ident = field.ident.as_ref().unwrap();
+                let name = ident.to_string();
+                quote! {
+                    .field(
+                        #name,
+                        &grub_split_library::deserialize::PrettyPrint::pretty(
+                            &self.#ident,
+                            depth - 1,
+                        ),
+                    )
+                }
+            });
+            quote! {
+                if depth == 0 {
+                    return f.write_str(concat!(#struct_name_str, " { .. }"));
+                }
+                f.debug_struct(#struct_name_str)
+                    #(#entries)*
+                    .finish()
+            }
+        }
+        Fields::Unnamed(ref fields) => {
+            let entries = (0..fields.unnamed.len()).map(|i| {
+                let index = Index::from(i);
+                quote! {
+                    .field(
+                        &grub_split_library::deserialize::PrettyPrint::pretty(
+                            &self.#index,
+                            depth - 1,
+                        ),
+                    )
+                }
+            });
+            quote! {
+                if depth == 0 {
+                    return f.write_str(concat!(#struct_name_str, "(..)"));
+                }
+                f.debug_tuple(#struct_name_str)
+                    #(#entries)*
+                    .finish()
+            }
+        }
+        Fields::Unit => quote! {
+            let _ = depth;
+            f.write_str(#struct_name_str)
+        },
+    };
+
+    // Like the standard derives, require each type parameter to be printable.
+    let type_params: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for param in type_params {
+        where_clause.predicates.push(parse_quote! {
+            #param: grub_split_library::deserialize::PrettyPrint
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics grub_split_library::deserialize::PrettyPrint for #struct_name #ty_generics #where_clause {
+            fn pretty_print(
+                &self,
+                f: &mut std::fmt::Formatter,
+                depth: usize,
+            ) -> std::fmt::Result {
+                #body
+            }
+        }
+
+        impl #impl_generics std::fmt::Debug for #struct_name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                grub_split_library::deserialize::PrettyPrint::pretty_print(
+                    self,
+                    f,
+                    grub_split_library::deserialize::DEFAULT_MAX_DEPTH,
+                )
+            }
+        }
+    }
+}
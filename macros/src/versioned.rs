@@ -0,0 +1,604 @@
+use proc_macro2::{Span, TokenStream};
+
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parenthesized, Attribute, Data, DeriveInput, GenericArgument, Generics,
+    Ident, Index, LitInt, LitStr, PathArguments, Token, Type, Visibility,
+};
+
+const ATTRIBUTE_NAME: &str = "versioned";
+
+const NUM_VERSION_COMPONENTS: usize = 4;
+
+type VersionComponents = [u16; NUM_VERSION_COMPONENTS];
+
+fn parse_version(literal: &LitStr) -> syn::Result<VersionComponents> {
+    let string = literal.value();
+    let parts: Vec<&str> = string.split('.').collect();
+    if parts.len() > NUM_VERSION_COMPONENTS {
+        return Err(syn::Error::new(
+            literal.span(),
+            "versions may have at most four components",
+        ));
+    }
+
+    let mut components = [0; NUM_VERSION_COMPONENTS];
+    for (component, part) in components.iter_mut().zip(parts) {
+        *component = part.parse().map_err(|_| {
+            syn::Error::new(literal.span(), "invalid version component")
+        })?;
+    }
+    Ok(components)
+}
+
+fn version_expr(components: VersionComponents) -> TokenStream {
+    let [major, minor, patch, build] = components;
+    quote! {
+        grub_split_library::deserialize::Version::new(
+            #major, #minor, #patch, #build,
+        )
+    }
+}
+
+// One item inside a `#[versioned(...)]` attribute.
+enum VersionedOption {
+    Since(LitStr),
+    Until(LitStr),
+    Offset(Punctuated<OffsetOverride, Token![,]>),
+}
+
+// A `"<version>" = <offset>` pair inside `offset(...)`.
+struct OffsetOverride {
+    version: LitStr,
+    offset: LitInt,
+}
+
+impl Parse for OffsetOverride {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let version = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let offset = input.parse()?;
+        Ok(Self { version, offset })
+    }
+}
+
+impl Parse for VersionedOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        if name == "since" || name == "until" {
+            input.parse::<Token![=]>()?;
+            let version = input.parse()?;
+            Ok(if name == "since" {
+                Self::Since(version)
+            } else {
+                Self::Until(version)
+            })
+        } else if name == "offset" {
+            let content;
+            parenthesized!(content in input);
+            Ok(Self::Offset(
+                content.parse_terminated(OffsetOverride::parse)?,
+            ))
+        } else {
+            Err(syn::Error::new(
+                name.span(),
+                "expected `since`, `until`, or `offset`",
+            ))
+        }
+    }
+}
+
+// Everything the derive needs to know about a single field.
+struct VersionedField {
+    member: TokenStream,
+    ident: Option<Ident>,
+    vis: Visibility,
+    ty: Type,
+    name: String,
+    // The type that is actually deserialized. For fields that are only present
+    // in some versions, this is the `T` in the field's `Option<T>` type.
+    inner_ty: Type,
+    optional: bool,
+    since: Option<VersionComponents>,
+    until: Option<VersionComponents>,
+    // Sorted in ascending order of version.
+    offsets: Vec<(VersionComponents, usize)>,
+}
+
+impl VersionedField {
+    fn new(index: usize, field: &syn::Field) -> syn::Result<Self> {
+        let mut since = None;
+        let mut until = None;
+        let mut offsets = Vec::new();
+
+        for attr in &field.attrs {
+            if !attr.path.is_ident(ATTRIBUTE_NAME) {
+                continue;
+            }
+            let options = attr.parse_args_with(
+                Punctuated::<VersionedOption, Token![,]>::parse_terminated,
+            )?;
+            for option in options {
+                match option {
+                    VersionedOption::Since(version) => {
+                        since = Some(parse_version(&version)?);
+                    }
+                    VersionedOption::Until(version) => {
+                        until = Some(parse_version(&version)?);
+                    }
+                    VersionedOption::Offset(overrides) => {
+                        for item in overrides {
+                            offsets.push((
+                                parse_version(&item.version)?,
+                                item.offset.base10_parse()?,
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        offsets.sort_unstable();
+
+        let optional = since.is_some() || until.is_some();
+        let inner_ty = if optional {
+            option_inner_type(&field.ty).ok_or_else(|| {
+                syn::Error::new(
+                    field.ty.span(),
+                    "fields with `since` or `until` must have type `Option<_>`",
+                )
+            })?
+        } else {
+            field.ty.clone()
+        };
+
+        let (member, name) = match field.ident {
+            Some(ref ident) => (quote!(#ident), ident.to_string()),
+            None => {
+                let index = Index::from(index);
+                (quote!(#index), index.index.to_string())
+            }
+        };
+
+        Ok(Self {
+            member,
+            ident: field.ident.clone(),
+            vis: field.vis.clone(),
+            ty: field.ty.clone(),
+            name,
+            inner_ty,
+            optional,
+            since,
+            until,
+            offsets,
+        })
+    }
+
+    // An expression that is true if this field is present in `version`.
+    fn present_expr(&self) -> TokenStream {
+        let since = self.since.map(version_expr).into_iter();
+        let until = self.until.map(version_expr).into_iter();
+        quote! {
+            true #(&& version >= #since)* #(&& version < #until)*
+        }
+    }
+
+    // An expression for this field's offset in `version` on architecture
+    // `arch`, given that the previous field ended at `next_offset`.
+    fn offset_expr(&self, arch: &TokenStream) -> TokenStream {
+        let ty = &self.inner_ty;
+        let overrides = self.offsets.iter().map(|(version, offset)| {
+            let version = version_expr(*version);
+            quote! {
+                if version >= #version {
+                    offset = #offset;
+                }
+            }
+        });
+        quote! {
+            {
+                let mut offset = grub_split_library::memory::Address::new(
+                    next_offset
+                ).align_forward(
+                    <#ty as grub_split_library::deserialize::VersionedDeserialize>
+                        ::alignment::<#arch>(version)
+                ).raw();
+                #(#overrides)*
+                offset
+            }
+        }
+    }
+
+    fn num_bytes_expr(&self, arch: &TokenStream) -> TokenStream {
+        let ty = &self.inner_ty;
+        quote! {
+            <#ty as grub_split_library::deserialize::VersionedDeserialize>
+                ::num_bytes::<#arch>(version)
+        }
+    }
+
+    // Wraps `body` so that it only runs if this field is present in `version`.
+    fn if_present(&self, body: &TokenStream) -> TokenStream {
+        if self.optional {
+            let present = self.present_expr();
+            quote! {
+                if #present {
+                    #body
+                }
+            }
+        } else {
+            quote!({ #body })
+        }
+    }
+}
+
+fn option_inner_type(ty: &Type) -> Option<Type> {
+    let Type::Path(ref path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(ref arguments) = segment.arguments else {
+        return None;
+    };
+    match arguments.args.first()? {
+        GenericArgument::Type(inner) => Some(inner.clone()),
+        _ => None,
+    }
+}
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    match try_expand(input) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn try_expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let struct_name = &input.ident;
+
+    let Data::Struct(ref struct_data) = input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "VersionedDeserialize can only be derived on structs",
+        ));
+    };
+
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    let fields = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| VersionedField::new(i, field))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let num_bytes = num_bytes_fn(&fields);
+    let alignment = alignment_fn(&fields);
+    let deserialize = deserialize_fn(&struct_name.to_string(), &fields);
+    let offset_of = offset_of_fn(&fields);
+    let remote = if parse_remote(&input.attrs)? {
+        remote_impl(struct_name, &input.generics, &fields)?
+    } else {
+        TokenStream::new()
+    };
+
+    Ok(quote! {
+        impl #impl_generics grub_split_library::deserialize::VersionedDeserialize for #struct_name #ty_generics #where_clause {
+            #num_bytes
+            #alignment
+            #deserialize
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #offset_of
+        }
+
+        #remote
+    })
+}
+
+// Returns whether the struct is annotated with `#[versioned(remote)]`.
+fn parse_remote(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut remote = false;
+    for attr in attrs {
+        if !attr.path.is_ident(ATTRIBUTE_NAME) {
+            continue;
+        }
+        let options = attr.parse_args_with(
+            Punctuated::<Ident, Token![,]>::parse_terminated,
+        )?;
+        for option in options {
+            if option != "remote" {
+                return Err(syn::Error::new(
+                    option.span(),
+                    "expected `remote`",
+                ));
+            }
+            remote = true;
+        }
+    }
+    Ok(remote)
+}
+
+// Generates an impl of `RemoteRef<Self>` with an accessor for each field that
+// reads only that field, laid out according to a given version.
+fn remote_impl(
+    struct_name: &Ident,
+    generics: &Generics,
+    fields: &[VersionedField],
+) -> syn::Result<TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let turbofish = ty_generics.as_turbofish();
+    let struct_name_str = struct_name.to_string();
+    let arch =
+        quote!(<M as grub_split_library::memory::MemoryReader>::Architecture);
+
+    let accessors = fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().ok_or_else(|| {
+                syn::Error::new(
+                    struct_name.span(),
+                    "remote accessors can only be generated for structs with \
+                     named fields",
+                )
+            })?;
+            let vis = &field.vis;
+            let ty = &field.ty;
+            let inner_ty = &field.inner_ty;
+            let field_name = &field.name;
+            let doc = format!(
+                "Reads the `{field_name}` field of the referenced \
+                 `{struct_name_str}` laid out according to `version`."
+            );
+            // Accessors for private fields may go unused.
+            let allow_dead_code = if matches!(vis, Visibility::Inherited) {
+                quote!(#[allow(dead_code)])
+            } else {
+                TokenStream::new()
+            };
+            let (check_present, wrap) = if field.optional {
+                let present = field.present_expr();
+                (
+                    quote! {
+                        if !(#present) {
+                            return Ok(None);
+                        }
+                    },
+                    quote!(.map(Some)),
+                )
+            } else {
+                (TokenStream::new(), TokenStream::new())
+            };
+            Ok(quote! {
+                #[doc = #doc]
+                #allow_dead_code
+                #vis fn #ident<M: grub_split_library::memory::MemoryReader>(
+                    &self,
+                    reader: &mut M,
+                    version: grub_split_library::deserialize::Version,
+                ) -> Result<#ty, grub_split_library::deserialize::Error> {
+                    #check_present
+                    let offset = #struct_name #turbofish::offset_of::<#arch>(
+                        version,
+                        #field_name,
+                    ).ok_or_else(|| {
+                        grub_split_library::deserialize::Error::InvalidStateError(
+                            format!(
+                                "{}.{} is not present in version {}",
+                                #struct_name_str,
+                                #field_name,
+                                version,
+                            )
+                        )
+                    })?;
+                    let address = self.address() + offset;
+                    <#inner_ty as grub_split_library::deserialize::VersionedDeserialize>
+                        ::deserialize_versioned(reader, address, version)
+                        .map_err(|err| err.within(
+                            grub_split_library::deserialize::PathSegment::Field {
+                                struct_name: #struct_name_str,
+                                field_name: #field_name,
+                                address,
+                            }
+                        ))
+                        #wrap
+                }
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        impl #impl_generics grub_split_library::deserialize::RemoteRef<#struct_name #ty_generics> #where_clause {
+            #(#accessors)*
+        }
+    })
+}
+
+fn num_bytes_fn(fields: &[VersionedField]) -> TokenStream {
+    let arch = quote!(A);
+    let steps = fields.iter().map(|field| {
+        let offset = field.offset_expr(&arch);
+        let num_bytes = field.num_bytes_expr(&arch);
+        field.if_present(&quote! {
+            next_offset = #offset + #num_bytes;
+        })
+    });
+
+    quote! {
+        fn num_bytes<A: grub_split_library::memory::Architecture>(
+            version: grub_split_library::deserialize::Version,
+        ) -> usize {
+            let mut next_offset: usize = 0;
+            #(#steps)*
+            next_offset
+        }
+    }
+}
+
+fn alignment_fn(fields: &[VersionedField]) -> TokenStream {
+    let steps = fields.iter().map(|field| {
+        let ty = &field.inner_ty;
+        field.if_present(&quote! {
+            alignment = std::cmp::max(
+                alignment,
+                <#ty as grub_split_library::deserialize::VersionedDeserialize>
+                    ::alignment::<A>(version),
+            );
+        })
+    });
+
+    quote! {
+        fn alignment<A: grub_split_library::memory::Architecture>(
+            version: grub_split_library::deserialize::Version,
+        ) -> usize {
+            let mut alignment: usize = 1;
+            #(#steps)*
+            alignment
+        }
+    }
+}
+
+fn deserialize_fn(struct_name: &str, fields: &[VersionedField]) -> TokenStream {
+    let identifiers: Vec<Ident> = (0..fields.len())
+        .map(|i| Ident::new(&format!("field{i}"), Span::mixed_site()))
+        .collect();
+
+    let arch =
+        quote!(<M as grub_split_library::memory::MemoryReader>::Architecture);
+
+    let initializers = fields.iter().zip(&identifiers).enumerate().map(
+        |(i, (field, ident))| {
+            let ty = &field.inner_ty;
+            let field_name = &field.name;
+            let offset = field.offset_expr(&arch);
+            let num_bytes = field.num_bytes_expr(&arch);
+            let advance = if i == fields.len() - 1 {
+                quote!()
+            } else {
+                quote!(next_offset = offset + #num_bytes;)
+            };
+            let read = quote! {
+                let offset = #offset;
+                let value = <#ty as grub_split_library::deserialize::VersionedDeserialize>
+                    ::deserialize_versioned(reader, address + offset, version)
+                    .map_err(|err| err.within(
+                        grub_split_library::deserialize::PathSegment::Field {
+                            struct_name: #struct_name,
+                            field_name: #field_name,
+                            address: address + offset,
+                        }
+                    ))?;
+                #advance
+            };
+            if field.optional {
+                let present = field.present_expr();
+                quote! {
+                    let #ident = if #present {
+                        #read
+                        Some(value)
+                    } else {
+                        None
+                    };
+                }
+            } else {
+                quote! {
+                    let #ident = {
+                        #read
+                        value
+                    };
+                }
+            }
+        },
+    );
+
+    let self_arguments =
+        fields.iter().zip(&identifiers).map(|(field, ident)| {
+            let member = &field.member;
+            quote!(#member: #ident)
+        });
+
+    let next_offset_binding = next_offset_binding(fields);
+
+    quote! {
+        fn deserialize_versioned<M: grub_split_library::memory::MemoryReader>(
+            reader: &mut M,
+            address: grub_split_library::memory::Address,
+            version: grub_split_library::deserialize::Version,
+        ) -> Result<Self, grub_split_library::deserialize::Error> {
+            // As in the `Deserialize` derive, read the whole struct at once
+            // and fall back to reading each field on its own.
+            let range = grub_split_library::memory::VariableLengthAddressRange {
+                start: address,
+                num_bytes: <Self as grub_split_library::deserialize::VersionedDeserialize>
+                    ::num_bytes::<#arch>(version),
+            };
+            let mut buffered_reader = match reader.read_vec(range) {
+                Ok(buffer) => grub_split_library::memory::buffered::BufferedMemoryReader::with_buffer(
+                    reader,
+                    address,
+                    buffer,
+                ),
+                Err(_) => grub_split_library::memory::buffered::BufferedMemoryReader::unbuffered(reader),
+            };
+            let reader = &mut buffered_reader;
+            #next_offset_binding
+            #(#initializers)*
+            Ok(Self { #(#self_arguments),* })
+        }
+    }
+}
+
+// The offset is only advanced between fields, so a struct with a single field
+// never reassigns it.
+fn next_offset_binding(fields: &[VersionedField]) -> TokenStream {
+    if fields.len() > 1 {
+        quote!(let mut next_offset: usize = 0;)
+    } else {
+        quote!(let next_offset: usize = 0;)
+    }
+}
+
+fn offset_of_fn(fields: &[VersionedField]) -> TokenStream {
+    let arch = quote!(A);
+    let steps = fields.iter().enumerate().map(|(i, field)| {
+        let field_name = &field.name;
+        let offset = field.offset_expr(&arch);
+        let num_bytes = field.num_bytes_expr(&arch);
+        let advance = if i == fields.len() - 1 {
+            quote!()
+        } else {
+            quote!(next_offset = offset + #num_bytes;)
+        };
+        field.if_present(&quote! {
+            let offset = #offset;
+            if field == #field_name {
+                return Some(offset);
+            }
+            #advance
+        })
+    });
+    let next_offset_binding = next_offset_binding(fields);
+
+    quote! {
+        /// Gets the offset of the field named `field` from the start of this
+        /// struct when the target has version `version` and architecture `A`.
+        ///
+        /// Returns `None` if no such field exists in `version`.
+        #[must_use]
+        pub fn offset_of<A: grub_split_library::memory::Architecture>(
+            version: grub_split_library::deserialize::Version,
+            field: &str,
+        ) -> Option<usize> {
+            #next_offset_binding
+            #(#steps)*
+            None
+        }
+    }
+}
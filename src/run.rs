@@ -3,18 +3,11 @@ use std::io;
 
 use log::{debug, trace};
 
-use grub_split_library::deserialize::LazyDeserialize;
 use grub_split_library::memory::caching::CachingMemoryReader;
 use grub_split_library::memory::external::{
     ExternalMemoryLocator, ExternalMemoryReader,
 };
-use grub_split_library::mono::{LoadedImages, MONO_TOKEN_TYPE_DEF};
-
-fn to_usize<T: TryInto<usize>>(
-    value: T,
-) -> Result<usize, <T as TryInto<usize>>::Error> {
-    value.try_into()
-}
+use grub_split_library::mono::{LoadedImages, MonoRuntime};
 
 pub fn run(pid: i32) -> Result<(), Box<dyn Error>> {
     trace!("Attaching to process");
@@ -26,59 +19,30 @@ pub fn run(pid: i32) -> Result<(), Box<dyn Error>> {
     trace!("Finding loaded images");
     let loaded_images = LoadedImages::new(&mut locator, &mut reader)?;
     trace!("Found loaded images");
-    let image =
-        loaded_images.get_image("Assembly-CSharp").ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Image not found")
-        })?;
-    trace!("Found image");
-    let ns_cache = image
-        .name_cache
-        .value
-        .as_ref()
+    let runtime = MonoRuntime::detect(&mut reader, &loaded_images)?;
+    debug!("Mono version is {}", runtime.version);
+    let image = loaded_images
+        .get_image(&mut reader, "Assembly-CSharp", runtime.version)?
         .ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Null namespace cache")
-        })?
-        .get("")
-        .ok_or_else(|| {
-            io::Error::new(io::ErrorKind::Other, "Empty namespace not found")
+            io::Error::other(format!(
+                "Image not found; loaded images are: {}",
+                loaded_images.image_names().collect::<Vec<_>>().join(", ")
+            ))
         })?;
-    trace!("Found ns cache");
-    let type_token =
-        to_usize(*ns_cache.value.get("GameManager").ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                "GameManager type token not found",
-            )
-        })?)?;
-    trace!("type token = {:x}", type_token);
-    let type_def_token = type_token + MONO_TOKEN_TYPE_DEF;
-    trace!("type def token = {:x}", type_def_token);
-
-    let class_cache_size = to_usize(image.class_cache.size)?;
-    let mut class = image
-        .class_cache
-        .table
-        .nth_element(&mut reader, type_def_token % class_cache_size)?
-        .value;
-    while to_usize(class.internals.type_token)? != type_def_token {
-        trace!(
-            "Found class {} with type def token {:x}",
-            &class.internals.name,
-            class.internals.type_token
-        );
-        let Some(ptr) = class.internals.next_class_cache else {
-            return Err(Box::new(io::Error::new(
-                io::ErrorKind::Other,
-                "Class cache entry not found",
-            )));
-        };
-        class = ptr.deref(&mut reader)?;
-    }
+    trace!("Found image");
+    let class = image
+        .find_class(&mut reader, "", "GameManager", &runtime)?
+        .ok_or_else(|| io::Error::other("GameManager class not found"))?;
     debug!("Found class with name {}", &class.internals.name);
 
-    let _instance_object =
-        class.get_static_field_object(&mut reader, "_instance")?;
+    let instance = class.get_static_field_object(&mut reader, "_instance")?;
     debug!("Found GameManager._instance");
 
+    let player_data = instance
+        .get_field_object(&mut reader, "playerData")?
+        .ok_or_else(|| io::Error::other("GameManager.playerData is null"))?;
+    let geo: i32 = player_data.get_field(&mut reader, "geo")?;
+    debug!("Found PlayerData.geo = {geo}");
+
     Ok(())
 }